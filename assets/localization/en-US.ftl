@@ -1,7 +1,89 @@
 language_name = English
+# Other languages can list the languages to use, in order, for messages they don't have yet.
+# English is always tried last. For example, Frisian (fy-NL.ftl) could use:
+# language_fallbacks = nl-NL
 
 greeting = Hello World!
 
 # ---- UI ----
 
-new_transport_line = New line
\ No newline at end of file
+new_transport_line = New line
+transport_line_drawing_hint = Click on planets to add stops to the line.
+confirm_transport_line = Create line
+cancel_transport_line = Cancel
+line_traversal_ping_pong = Back and forth
+line_traversal_loop = Loop
+transport_line_ship_speed = Ship speed
+transport_line_stop_count = { $count ->
+    [one] { $count } stop
+   *[other] { $count } stops
+}
+transport_summary = { $lines ->
+    [one] { $lines } line
+   *[other] { $lines } lines
+}, { $ships ->
+    [one] { $ships } ship
+   *[other] { $ships } ships
+}
+
+planet_stock = { $good }: { $amount }
+planet_visiting_ships = { $count ->
+    [0] No ships visit this planet
+    [one] { $count } ship visits this planet
+   *[other] { $count } ships visit this planet
+}
+
+# ---- Goods ----
+
+good_food = Food
+good_ore = Ore
+good_fuel = Fuel
+
+# ---- Menus ----
+
+menu_new_game = New game
+menu_load_game = Load game
+menu_save_game = Save game
+menu_settings = Settings
+menu_quit = Quit
+menu_paused = Paused
+menu_resume = Resume
+menu_main_menu = Main menu
+menu_back = Back
+settings_language = Language
+menu_controls = Controls
+
+controls_press_input = Press a key or click outside this window...
+controls_binding_hint = Click to change, right-click to remove.
+controls_conflict = Also used for: { $actions }
+controls_reset = Reset to defaults
+controls_cancel = Cancel
+
+# ---- Actions ----
+
+action_hello = Say hello
+action_primary_interaction = Select
+action_cursor_move = Move cursor
+action_pause = Pause
+action_quick_save = Quicksave
+action_quick_load = Quickload
+action_camera_up = Move camera up
+action_camera_down = Move camera down
+action_camera_left = Move camera left
+action_camera_right = Move camera right
+action_camera_zoom_in = Zoom in
+action_camera_zoom_out = Zoom out
+action_camera_pan = Pan camera
+action_camera_zoom_in_hold = Zoom in (hold)
+action_camera_zoom_out_hold = Zoom out (hold)
+action_camera_drag = Drag camera
+action_camera_toggle_edge_scrolling = Toggle edge scrolling
+action_camera_focus_selected = Focus on selection
+action_camera_follow = Follow
+action_camera_store_bookmark = Store bookmark { $slot }
+action_camera_recall_bookmark = Go to bookmark { $slot }
+
+# ---- Problems ----
+
+localization_warnings = Problems with the translations
+localization_warnings_dismiss = OK
@@ -0,0 +1,89 @@
+//! Generates the `MessageId` enum from the English localization file, so that a typo in a
+//! message id fails the build instead of showing up as a warning at runtime.
+//! The generated code is included by `src/localization/message_ids.rs`.
+
+use fluent_syntax::ast::Entry;
+use fluent_syntax::parser;
+use std::fmt::Write;
+use std::path::Path;
+use std::{env, fs};
+
+/// All other languages are translations of this file, so it has every message the game uses.
+const REFERENCE_LANGUAGE_FILE: &str = "assets/localization/en-US.ftl";
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", REFERENCE_LANGUAGE_FILE);
+
+    let source = fs::read_to_string(REFERENCE_LANGUAGE_FILE)
+        .unwrap_or_else(|error| panic!("Could not read `{}`: {}", REFERENCE_LANGUAGE_FILE, error));
+    let resource = parser::parse(source.as_str()).unwrap_or_else(|(_, errors)| {
+        panic!(
+            "Could not parse `{}`: {:?}",
+            REFERENCE_LANGUAGE_FILE, errors
+        )
+    });
+
+    let message_ids: Vec<&str> = resource
+        .body
+        .iter()
+        .filter_map(|entry| match entry {
+            Entry::Message(message) => Some(message.id.name),
+            _ => None,
+        })
+        .collect();
+
+    let out_path = Path::new(&env::var("OUT_DIR").unwrap()).join("message_ids.rs");
+    fs::write(out_path, generate_message_ids(&message_ids)).unwrap();
+}
+
+fn generate_message_ids(message_ids: &[&str]) -> String {
+    let mut code = String::new();
+
+    writeln!(
+        code,
+        "/// The id of a message in `{}`.",
+        REFERENCE_LANGUAGE_FILE
+    )
+    .unwrap();
+    writeln!(code, "#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]").unwrap();
+    writeln!(code, "pub enum MessageId {{").unwrap();
+    for id in message_ids {
+        writeln!(code, "    /// `{}`", id).unwrap();
+        writeln!(code, "    {},", variant_name(id)).unwrap();
+    }
+    writeln!(code, "}}").unwrap();
+
+    writeln!(code, "impl MessageId {{").unwrap();
+    writeln!(code, "    /// The id as it is written in the `.ftl` files.").unwrap();
+    writeln!(code, "    pub fn as_str(self) -> &'static str {{").unwrap();
+    writeln!(code, "        match self {{").unwrap();
+    for id in message_ids {
+        writeln!(
+            code,
+            "            MessageId::{} => \"{}\",",
+            variant_name(id),
+            id
+        )
+        .unwrap();
+    }
+    writeln!(code, "        }}").unwrap();
+    writeln!(code, "    }}").unwrap();
+    writeln!(code, "}}").unwrap();
+
+    code
+}
+
+/// Turns a message id like `menu_new_game` into an enum variant name like `MenuNewGame`.
+fn variant_name(message_id: &str) -> String {
+    message_id
+        .split(|c| c == '_' || c == '-')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut characters = part.chars();
+            match characters.next() {
+                Some(first) => first.to_uppercase().chain(characters).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
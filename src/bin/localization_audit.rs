@@ -0,0 +1,93 @@
+//! Compares every language file with English, and lists the messages that are missing,
+//! left over, or that use different variables. Problems in the files themselves are listed too.
+//! Exits with an error if any language is incomplete or has problems, so it can be used in CI.
+//!
+//! Usage: `cargo run --bin localization_audit [directory]`.
+//! The directory defaults to `assets/localization`.
+
+#![forbid(unsafe_code)]
+#![warn(clippy::all, rust_2018_idioms)]
+
+use color_eyre::eyre::{bail, eyre, Result};
+use space_bees::localization::{audit_language, load_language_from_fluent_string};
+use std::path::PathBuf;
+use std::{env, fs};
+
+const DEFAULT_LOCALIZATION_DIRECTORY: &str = "assets/localization";
+
+/// The language all other languages are compared with.
+const REFERENCE_LANGUAGE: &str = "en-US";
+
+fn main() -> Result<()> {
+    color_eyre::install()?;
+
+    let directory = env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_LOCALIZATION_DIRECTORY));
+
+    let mut reference = None;
+    let mut broken_count = 0;
+    let mut languages = vec![];
+    for entry in fs::read_dir(&directory)? {
+        let path = entry?.path();
+        if path
+            .extension()
+            .map_or(true, |extension| extension != "ftl")
+        {
+            continue;
+        }
+
+        let language_id = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| eyre!("`{}` has no valid file name", path.display()))?;
+        let language = load_language_from_fluent_string(fs::read_to_string(&path)?, language_id)
+            .map_err(|error| eyre!("Could not load `{}`: {}", path.display(), error))?;
+
+        if !language.load_errors().is_empty() {
+            println!(
+                "`{}` has problems:{}",
+                path.display(),
+                language.load_errors_listing()
+            );
+            broken_count += 1;
+        }
+
+        if language_id == REFERENCE_LANGUAGE {
+            reference = Some(language);
+        } else {
+            languages.push(language);
+        }
+    }
+
+    let reference = reference.ok_or_else(|| {
+        eyre!(
+            "No `{}.ftl` in `{}`",
+            REFERENCE_LANGUAGE,
+            directory.display()
+        )
+    })?;
+    languages.sort_by_key(|language| language.identifier().to_string());
+
+    let mut incomplete_count = 0;
+    for language in &languages {
+        let audit = audit_language(&reference, language);
+        if !audit.is_complete() {
+            incomplete_count += 1;
+        }
+        print!("{}", audit);
+    }
+
+    if broken_count > 0 {
+        bail!("{} language files have problems", broken_count);
+    }
+    if incomplete_count > 0 {
+        bail!(
+            "{} of {} languages are incomplete",
+            incomplete_count,
+            languages.len()
+        );
+    }
+    Ok(())
+}
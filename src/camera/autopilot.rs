@@ -0,0 +1,144 @@
+use crate::camera::{CameraBounds, CameraZoom, MainCamera, CLOSEST_ZOOM, FARTHEST_ZOOM};
+use crate::input::{Action, ActionRes, GlobalCursorPosition};
+use crate::transport::Ship;
+use crate::{planet_under_cursor, Planet, Selected};
+use bevy::math::Vec3Swizzles;
+use bevy::prelude::*;
+
+/// How quickly the camera catches up with where the autopilot takes it. Higher is faster.
+const AUTOPILOT_SMOOTHING: f32 = 6.0;
+/// Once the camera is this close to where it is focusing, in game units, it jumps the rest of the way.
+const FOCUS_SNAP_DISTANCE: f32 = 0.5;
+/// Room around the selection when focusing on it, in game units.
+const FOCUS_MARGIN: f32 = 100.0;
+/// How close the cursor has to be to a ship to follow it, in logical pixels.
+const SHIP_PICK_RADIUS: f32 = 12.0;
+
+/// Where the camera is moving by itself, instead of being moved by the player.
+/// Panning the camera in any way hands control back to the player.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Debug)]
+pub enum CameraAutopilot {
+    #[default]
+    Off,
+    /// Moving to a point, after which the autopilot turns off.
+    Focus { position: Vec2 },
+    /// Keeping an entity in the center of the screen, for as long as it exists.
+    Follow { entity: Entity },
+}
+
+impl CameraAutopilot {
+    pub fn is_off(&self) -> bool {
+        *self == CameraAutopilot::Off
+    }
+}
+
+/// Starts focusing on the selected planets, or following what the cursor is on.
+#[allow(clippy::too_many_arguments)]
+pub fn camera_autopilot_actions_system(
+    actions: ActionRes,
+    mut autopilot: ResMut<CameraAutopilot>,
+    mut camera_query: Query<(&OrthographicProjection, &mut CameraZoom), With<MainCamera>>,
+    windows: Res<Windows>,
+    cursor_position: Res<GlobalCursorPosition>,
+    selected_planets: Query<(Entity, &GlobalTransform, &Planet), With<Selected>>,
+    planets: Query<(Entity, &GlobalTransform, &Planet)>,
+    ships: Query<(Entity, &GlobalTransform), With<Ship>>,
+) {
+    let Ok((projection, mut zoom)) = camera_query.get_single_mut() else { return; };
+
+    if actions.just_pressed(Action::CameraFocusSelected) && !selected_planets.is_empty() {
+        let positions = selected_planets
+            .iter()
+            .map(|(_, transform, _)| transform.translation().xy())
+            .collect::<Vec<_>>();
+        let centroid = positions.iter().sum::<Vec2>() / positions.len() as f32;
+
+        // The view is centered on the centroid, so it has to reach as far as the farthest planet
+        // in both directions.
+        let half_size =
+            selected_planets
+                .iter()
+                .fold(Vec2::ZERO, |half_size, (_, transform, planet)| {
+                    let reach = (transform.translation().xy() - centroid).abs() + planet.radius;
+                    half_size.max(reach)
+                });
+        if let Some(window) = windows.get_primary() {
+            let window_size = Vec2::new(window.width(), window.height());
+            let scale_to_fit = ((half_size + FOCUS_MARGIN) * 2.0 / window_size).max_element();
+            zoom.target_scale = scale_to_fit.clamp(CLOSEST_ZOOM, FARTHEST_ZOOM);
+        }
+
+        *autopilot = CameraAutopilot::Focus { position: centroid };
+    }
+
+    if actions.just_pressed(Action::CameraFollow) {
+        if let CameraAutopilot::Follow { .. } = *autopilot {
+            *autopilot = CameraAutopilot::Off;
+            return;
+        }
+
+        let pick_radius = SHIP_PICK_RADIUS * projection.scale;
+        let ship_under_cursor = ships
+            .iter()
+            .map(|(entity, transform)| {
+                let distance = cursor_position.distance(transform.translation().xy());
+                (entity, distance)
+            })
+            .filter(|(_, distance)| *distance < pick_radius)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(entity, _)| entity);
+        let target = ship_under_cursor
+            .or_else(|| planet_under_cursor(**cursor_position, planets.iter()))
+            .or_else(|| selected_planets.iter().next().map(|(entity, _, _)| entity));
+
+        if let Some(entity) = target {
+            *autopilot = CameraAutopilot::Follow { entity };
+        }
+    }
+}
+
+/// Moves the camera smoothly towards where the [CameraAutopilot] is taking it.
+/// Targets near the edge of the [CameraBounds] are moved to where the camera can actually get to
+/// once it is done zooming, so that focusing on them ends.
+pub fn camera_autopilot_system(
+    mut camera_query: Query<(&mut Transform, &CameraZoom), With<MainCamera>>,
+    mut autopilot: ResMut<CameraAutopilot>,
+    targets: Query<&GlobalTransform>,
+    bounds: Res<CameraBounds>,
+    windows: Res<Windows>,
+    time: Res<Time>,
+) {
+    let target = match *autopilot {
+        CameraAutopilot::Off => return,
+        CameraAutopilot::Focus { position } => position,
+        CameraAutopilot::Follow { entity } => match targets.get(entity) {
+            Ok(transform) => transform.translation().xy(),
+            Err(_) => {
+                // Whatever we were following is gone.
+                *autopilot = CameraAutopilot::Off;
+                return;
+            }
+        },
+    };
+    let Ok((mut transform, zoom)) = camera_query.get_single_mut() else { return; };
+    let target = match windows.get_primary() {
+        Some(window) => bounds.clamp(
+            target,
+            zoom.target_scale,
+            Vec2::new(window.width(), window.height()),
+        ),
+        None => target,
+    };
+
+    let blend = 1.0 - (-AUTOPILOT_SMOOTHING * time.delta_seconds()).exp();
+    let mut position = transform.translation.xy().lerp(target, blend);
+    if let CameraAutopilot::Focus { .. } = *autopilot {
+        if position.distance(target) < FOCUS_SNAP_DISTANCE {
+            position = target;
+            *autopilot = CameraAutopilot::Off;
+        }
+    }
+
+    transform.translation.x = position.x;
+    transform.translation.y = position.y;
+}
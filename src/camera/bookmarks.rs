@@ -0,0 +1,50 @@
+use crate::camera::{CameraAutopilot, CameraZoom, MainCamera};
+use crate::input::{Action, ActionRes};
+use bevy::math::Vec3Swizzles;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub const BOOKMARK_SLOTS: usize = 9;
+
+/// A camera position and zoom level that the player stored, to return to later.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct CameraBookmark {
+    pub position: Vec2,
+    pub scale: f32,
+}
+
+/// The player's bookmarks, by slot. Kept in the save file, because they are places in that game.
+#[derive(Resource, Serialize, Deserialize, Default, Clone, PartialEq, Debug)]
+pub struct CameraBookmarks {
+    slots: [Option<CameraBookmark>; BOOKMARK_SLOTS],
+}
+
+/// Stores the current view in a bookmark slot, or moves the camera to a stored one.
+pub fn camera_bookmark_system(
+    actions: ActionRes,
+    mut bookmarks: ResMut<CameraBookmarks>,
+    mut autopilot: ResMut<CameraAutopilot>,
+    mut camera_query: Query<(&Transform, &mut CameraZoom), With<MainCamera>>,
+) {
+    let Ok((transform, mut zoom)) = camera_query.get_single_mut() else { return; };
+
+    let slot_actions = Action::STORE_BOOKMARK
+        .into_iter()
+        .zip(Action::RECALL_BOOKMARK);
+    for (slot, (store_action, recall_action)) in slot_actions.enumerate() {
+        if actions.just_pressed(store_action) {
+            // The target scale, so that a zoom that is still going on is stored as it will end up.
+            bookmarks.slots[slot] = Some(CameraBookmark {
+                position: transform.translation.xy(),
+                scale: zoom.target_scale,
+            });
+        } else if actions.just_pressed(recall_action) {
+            let Some(bookmark) = bookmarks.slots[slot] else { continue; };
+
+            *autopilot = CameraAutopilot::Focus {
+                position: bookmark.position,
+            };
+            zoom.target_scale = bookmark.scale;
+        }
+    }
+}
@@ -0,0 +1,486 @@
+mod autopilot;
+mod bookmarks;
+
+use crate::camera::autopilot::{camera_autopilot_actions_system, camera_autopilot_system};
+use crate::camera::bookmarks::camera_bookmark_system;
+use crate::input::{
+    update_global_cursor_position, Action, ActionRes, GlobalCursorPosition, UiInputClaims,
+    VirtualCursor,
+};
+use crate::{GameState, Planet};
+use bevy::core_pipeline::clear_color::ClearColorConfig;
+use bevy::ecs::schedule::ShouldRun;
+use bevy::prelude::*;
+
+pub use crate::camera::autopilot::CameraAutopilot;
+pub use crate::camera::bookmarks::{CameraBookmark, CameraBookmarks, BOOKMARK_SLOTS};
+
+/// Speed in game units per second.
+const CAMERA_SPEED: f32 = 500.0;
+
+const CLOSEST_ZOOM: f32 = 1.0;
+const FARTHEST_ZOOM: f32 = 5.0;
+const ZOOM_SPEED_FACTOR: f32 = 0.2;
+/// Factor by which the zoom changes per second while a zoom trigger is fully pressed.
+const HOLD_ZOOM_FACTOR_PER_SECOND: f32 = 2.5;
+/// How quickly the zoom catches up with its target. Higher is faster.
+const ZOOM_SMOOTHING: f32 = 12.0;
+/// Once the zoom is this close to its target (relative to the target), it jumps the rest of the way.
+const ZOOM_SNAP_DISTANCE: f32 = 0.001;
+/// Distance from the edge of the window, in logical pixels, within which the cursor scrolls the camera.
+const EDGE_SCROLL_MARGIN: f32 = 8.0;
+/// How far, in game units, the camera can look past the outermost planets.
+const CAMERA_BOUNDS_MARGIN: f32 = 300.0;
+
+pub struct GameCameraPlugin;
+
+impl Plugin for GameCameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(EdgeScrolling::default())
+            .insert_resource(CameraBounds::default())
+            .insert_resource(CameraAutopilot::default())
+            .insert_resource(CameraBookmarks::default())
+            .add_system(camera_bounds_update_system)
+            .add_startup_system(camera_setup_system)
+            .add_system_set(SystemSet::on_exit(GameState::Main).with_system(camera_reset_system))
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(camera_controls_active)
+                    .with_system(camera_movement_system)
+                    .with_system(edge_scrolling_toggle_system)
+                    .with_system(camera_zoom_system.after(camera_bounds_update_system))
+                    // Both depend on the cursor position of this frame, to not overshoot.
+                    .with_system(
+                        camera_zoom_smoothing_system
+                            .after(camera_zoom_system)
+                            .after(update_global_cursor_position),
+                    )
+                    .with_system(
+                        camera_drag_system
+                            .after(camera_zoom_smoothing_system)
+                            .after(update_global_cursor_position),
+                    )
+                    .with_system(camera_autopilot_actions_system)
+                    .with_system(camera_bookmark_system)
+                    .with_system(
+                        camera_autopilot_system
+                            .after(camera_autopilot_actions_system)
+                            .after(camera_bookmark_system)
+                            .after(camera_movement_system)
+                            .after(camera_drag_system),
+                    )
+                    .with_system(
+                        camera_bounds_system
+                            .after(camera_bounds_update_system)
+                            .after(camera_autopilot_system),
+                    ),
+            );
+    }
+}
+
+#[derive(Component)]
+pub struct MainCamera;
+
+/// The zoom level the camera is moving towards. The scale of the projection follows it smoothly.
+#[derive(Component)]
+pub struct CameraZoom {
+    pub target_scale: f32,
+}
+
+/// Whether moving the cursor to the edge of the window scrolls the camera.
+#[derive(Resource)]
+pub struct EdgeScrolling {
+    pub enabled: bool,
+}
+
+impl Default for EdgeScrolling {
+    fn default() -> Self {
+        EdgeScrolling { enabled: true }
+    }
+}
+
+/// The area the camera has to stay within, so that the player can't get lost in empty space:
+/// all planets, plus [CAMERA_BOUNDS_MARGIN]. `None` while there are no planets.
+#[derive(Resource, Default)]
+pub struct CameraBounds {
+    pub area: Option<Rect>,
+}
+
+impl CameraBounds {
+    /// The farthest the camera can zoom out: until all of the bounds are in view.
+    fn farthest_zoom(&self, window_size: Vec2) -> f32 {
+        match self.area {
+            Some(area) => (area.size() / window_size)
+                .max_element()
+                .clamp(CLOSEST_ZOOM, FARTHEST_ZOOM),
+            None => FARTHEST_ZOOM,
+        }
+    }
+
+    /// The position closest to `position` where the camera keeps the view within the bounds,
+    /// at the given scale.
+    fn clamp(&self, position: Vec2, scale: f32, window_size: Vec2) -> Vec2 {
+        let Some(area) = self.area else { return position; };
+
+        let half_view = window_size / 2.0 * scale;
+        let lowest = area.min + half_view;
+        let highest = area.max - half_view;
+        let center = area.center();
+        Vec2::new(
+            clamp_or_center(position.x, lowest.x, highest.x, center.x),
+            clamp_or_center(position.y, lowest.y, highest.y, center.y),
+        )
+    }
+}
+
+fn camera_setup_system(mut commands: Commands) {
+    commands
+        .spawn(Camera2dBundle {
+            camera_2d: Camera2d {
+                clear_color: ClearColorConfig::Custom(Color::BLACK),
+            },
+            ..default()
+        })
+        .insert(MainCamera)
+        .insert(CameraZoom {
+            target_scale: CLOSEST_ZOOM,
+        });
+}
+
+/// The camera can also be moved while the game is paused, so that the player can inspect the map.
+fn camera_controls_active(state: Res<State<GameState>>) -> ShouldRun {
+    match state.current() {
+        GameState::Main | GameState::Paused => ShouldRun::Yes,
+        _ => ShouldRun::No,
+    }
+}
+
+/// Puts the camera back in its starting position, ready for the next game.
+fn camera_reset_system(
+    mut camera_query: Query<
+        (&mut Transform, &mut OrthographicProjection, &mut CameraZoom),
+        With<MainCamera>,
+    >,
+    mut autopilot: ResMut<CameraAutopilot>,
+    mut bookmarks: ResMut<CameraBookmarks>,
+) {
+    *autopilot = CameraAutopilot::Off;
+    *bookmarks = CameraBookmarks::default();
+
+    let Ok((mut transform, mut projection, mut zoom)) = camera_query.get_single_mut() else { return; };
+
+    transform.translation.x = 0.0;
+    transform.translation.y = 0.0;
+    projection.scale = CLOSEST_ZOOM;
+    zoom.target_scale = CLOSEST_ZOOM;
+}
+
+/// Changes the zoom level the camera moves towards. See [camera_zoom_smoothing_system].
+fn camera_zoom_system(
+    mut camera_query: Query<&mut CameraZoom, With<MainCamera>>,
+    time: Res<Time>,
+    actions: ActionRes,
+    bounds: Res<CameraBounds>,
+    windows: Res<Windows>,
+) {
+    let Ok(mut zoom) = camera_query.get_single_mut() else { return; };
+
+    let mut target_scale = zoom.target_scale;
+    if actions.just_pressed(Action::CameraZoomIn) {
+        target_scale -= target_scale * ZOOM_SPEED_FACTOR;
+    }
+    if actions.just_pressed(Action::CameraZoomOut) {
+        target_scale += target_scale * ZOOM_SPEED_FACTOR;
+    }
+
+    // Analog triggers zoom faster the further they are pressed.
+    let hold_zoom =
+        actions.value(Action::CameraZoomOutHold) - actions.value(Action::CameraZoomInHold);
+    target_scale *= HOLD_ZOOM_FACTOR_PER_SECOND.powf(hold_zoom * time.delta_seconds());
+
+    let farthest_zoom = windows
+        .get_primary()
+        .map(|window| bounds.farthest_zoom(Vec2::new(window.width(), window.height())))
+        .unwrap_or(FARTHEST_ZOOM);
+    let target_scale = target_scale.clamp(CLOSEST_ZOOM, farthest_zoom);
+    if target_scale != zoom.target_scale {
+        zoom.target_scale = target_scale;
+    }
+}
+
+/// Moves the scale of the camera towards the target of the [CameraZoom], while keeping the
+/// point under the cursor in place, so that the player can zoom in on what they point at.
+fn camera_zoom_smoothing_system(
+    mut camera_query: Query<
+        (&mut Transform, &mut OrthographicProjection, &CameraZoom),
+        With<MainCamera>,
+    >,
+    time: Res<Time>,
+    windows: Res<Windows>,
+    cursor_position: Res<GlobalCursorPosition>,
+    virtual_cursor: Res<VirtualCursor>,
+    autopilot: Res<CameraAutopilot>,
+) {
+    let Ok((mut transform, mut projection, zoom)) = camera_query.get_single_mut() else { return; };
+    if projection.scale == zoom.target_scale {
+        return;
+    }
+
+    let old_scale = projection.scale;
+    let blend = 1.0 - (-ZOOM_SMOOTHING * time.delta_seconds()).exp();
+    let mut new_scale = old_scale + (zoom.target_scale - old_scale) * blend;
+    if (zoom.target_scale - new_scale).abs() < ZOOM_SNAP_DISTANCE * zoom.target_scale {
+        new_scale = zoom.target_scale;
+    }
+    projection.scale = new_scale;
+
+    // When the cursor is outside the window, or the autopilot is steering, zoom towards the center
+    // of the screen instead.
+    let cursor_in_window = virtual_cursor.position().is_some()
+        || windows
+            .get_primary()
+            .and_then(|window| window.cursor_position())
+            .is_some();
+    if cursor_in_window && autopilot.is_off() {
+        let position = zoom_around(
+            transform.translation.truncate(),
+            **cursor_position,
+            old_scale,
+            new_scale,
+        );
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn camera_movement_system(
+    mut camera_query: Query<(&mut Transform, &OrthographicProjection), With<MainCamera>>,
+    time: Res<Time>,
+    actions: ActionRes,
+    windows: Res<Windows>,
+    virtual_cursor: Res<VirtualCursor>,
+    ui_input_claims: Res<UiInputClaims>,
+    edge_scrolling: Res<EdgeScrolling>,
+    mut autopilot: ResMut<CameraAutopilot>,
+) {
+    let Ok((mut transform, projection)) = camera_query.get_single_mut() else { return; };
+
+    let mut movement_per_second = Vec2::default();
+
+    if actions.pressed(Action::CameraUp) {
+        movement_per_second.y += CAMERA_SPEED;
+    }
+    if actions.pressed(Action::CameraDown) {
+        movement_per_second.y -= CAMERA_SPEED;
+    }
+    if actions.pressed(Action::CameraLeft) {
+        movement_per_second.x -= CAMERA_SPEED;
+    }
+    if actions.pressed(Action::CameraRight) {
+        movement_per_second.x += CAMERA_SPEED;
+    }
+    if actions.pressed(Action::CameraPan) {
+        if let Some(stick) = actions.axis_pair(Action::CameraPan) {
+            movement_per_second += stick.xy() * CAMERA_SPEED;
+        }
+    }
+    // Don't scroll away while the player is using a window at the edge of the screen.
+    if edge_scrolling.enabled && !ui_input_claims.pointer {
+        movement_per_second += edge_scroll_direction(&windows, &virtual_cursor) * CAMERA_SPEED;
+    }
+
+    if movement_per_second == Vec2::ZERO {
+        return;
+    }
+    // The player takes over from the autopilot.
+    if !autopilot.is_off() {
+        *autopilot = CameraAutopilot::Off;
+    }
+
+    // Scaled with the zoom, so that the map moves across the screen at the same speed at every
+    // zoom level.
+    let movement = movement_per_second * projection.scale * time.delta_seconds();
+    transform.translation.x += movement.x;
+    transform.translation.y += movement.y;
+}
+
+/// The direction towards the edges of the window that the cursor is close to, if any.
+fn edge_scroll_direction(windows: &Windows, virtual_cursor: &VirtualCursor) -> Vec2 {
+    let Some(window) = windows.get_primary() else { return Vec2::ZERO; };
+    if !window.is_focused() {
+        return Vec2::ZERO;
+    }
+    let position = virtual_cursor
+        .position()
+        .or_else(|| window.cursor_position());
+    let Some(position) = position else { return Vec2::ZERO; };
+
+    let mut direction = Vec2::ZERO;
+    if position.x < EDGE_SCROLL_MARGIN {
+        direction.x -= 1.0;
+    }
+    if position.x > window.width() - EDGE_SCROLL_MARGIN {
+        direction.x += 1.0;
+    }
+    // Window coordinates start at the bottom.
+    if position.y < EDGE_SCROLL_MARGIN {
+        direction.y -= 1.0;
+    }
+    if position.y > window.height() - EDGE_SCROLL_MARGIN {
+        direction.y += 1.0;
+    }
+    direction.normalize_or_zero()
+}
+
+fn edge_scrolling_toggle_system(actions: ActionRes, mut edge_scrolling: ResMut<EdgeScrolling>) {
+    if actions.just_pressed(Action::CameraToggleEdgeScrolling) {
+        edge_scrolling.enabled = !edge_scrolling.enabled;
+    }
+}
+
+/// Moves the camera so that the point where the player grabbed the world stays under the cursor.
+fn camera_drag_system(
+    mut camera_query: Query<&mut Transform, With<MainCamera>>,
+    actions: ActionRes,
+    cursor_position: Res<GlobalCursorPosition>,
+    mut grabbed_point: Local<Option<Vec2>>,
+    mut autopilot: ResMut<CameraAutopilot>,
+) {
+    if !actions.pressed(Action::CameraDrag) {
+        *grabbed_point = None;
+        return;
+    }
+    let Ok(mut transform) = camera_query.get_single_mut() else { return; };
+
+    // The player takes over from the autopilot.
+    if !autopilot.is_off() {
+        *autopilot = CameraAutopilot::Off;
+    }
+
+    let grabbed = *grabbed_point.get_or_insert(**cursor_position);
+    let offset = grabbed - **cursor_position;
+    transform.translation.x += offset.x;
+    transform.translation.y += offset.y;
+}
+
+/// Recalculates the [CameraBounds] when planets are added or removed.
+fn camera_bounds_update_system(
+    mut bounds: ResMut<CameraBounds>,
+    planets: Query<(&Transform, &Planet)>,
+    added_planets: Query<(), Added<Planet>>,
+    removed_planets: RemovedComponents<Planet>,
+) {
+    if added_planets.is_empty() && removed_planets.iter().next().is_none() {
+        return;
+    }
+
+    bounds.area = planets
+        .iter()
+        .map(|(transform, planet)| {
+            Rect::from_center_half_size(
+                transform.translation.truncate(),
+                Vec2::splat(planet.radius),
+            )
+        })
+        .reduce(|area, planet_area| area.union(planet_area))
+        .map(|area| area.inset(CAMERA_BOUNDS_MARGIN));
+}
+
+/// Keeps the view within the [CameraBounds].
+/// Along axes where the view is bigger than the bounds, the camera stays in the middle.
+fn camera_bounds_system(
+    mut camera_query: Query<(&mut Transform, &OrthographicProjection), With<MainCamera>>,
+    bounds: Res<CameraBounds>,
+    windows: Res<Windows>,
+) {
+    let Some(window) = windows.get_primary() else { return; };
+    let Ok((mut transform, projection)) = camera_query.get_single_mut() else { return; };
+
+    let window_size = Vec2::new(window.width(), window.height());
+    let position = transform.translation.truncate();
+    let clamped = bounds.clamp(position, projection.scale, window_size);
+    if clamped != position {
+        transform.translation.x = clamped.x;
+        transform.translation.y = clamped.y;
+    }
+}
+
+/// Where the camera has to be after changing its scale from `old_scale` to `new_scale`,
+/// so that the world point `anchor` stays at the same place on the screen.
+fn zoom_around(position: Vec2, anchor: Vec2, old_scale: f32, new_scale: f32) -> Vec2 {
+    anchor + (position - anchor) * (new_scale / old_scale)
+}
+
+/// Clamps the value between `lowest` and `highest`, or returns `center` if the range is empty.
+fn clamp_or_center(value: f32, lowest: f32, highest: f32, center: f32) -> f32 {
+    if lowest > highest {
+        center
+    } else {
+        value.clamp(lowest, highest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds_keep_the_view_inside() {
+        let bounds = CameraBounds {
+            area: Some(Rect::new(-500.0, -500.0, 500.0, 500.0)),
+        };
+        let window_size = Vec2::new(200.0, 100.0);
+
+        assert_eq!(
+            bounds.clamp(Vec2::new(1000.0, -20.0), 1.0, window_size),
+            Vec2::new(400.0, -20.0)
+        );
+        assert_eq!(
+            bounds.clamp(Vec2::new(-1000.0, -1000.0), 2.0, window_size),
+            Vec2::new(-300.0, -400.0)
+        );
+    }
+
+    #[test]
+    fn view_larger_than_bounds_is_centered() {
+        let bounds = CameraBounds {
+            area: Some(Rect::new(0.0, 0.0, 100.0, 1000.0)),
+        };
+
+        assert_eq!(
+            bounds.clamp(Vec2::new(80.0, 900.0), 1.0, Vec2::new(200.0, 100.0)),
+            Vec2::new(50.0, 900.0)
+        );
+    }
+
+    #[test]
+    fn zooming_keeps_the_anchor_in_place() {
+        let position = Vec2::new(100.0, -50.0);
+        // Where the cursor is on the screen, in pixels from the center.
+        let cursor_offset = Vec2::new(-120.0, 80.0);
+        for (old_scale, new_scale) in [(1.0, 0.5), (2.0, 3.5), (0.8, 0.8)] {
+            let anchor = position + cursor_offset * old_scale;
+
+            let new_position = zoom_around(position, anchor, old_scale, new_scale);
+
+            let new_offset = (anchor - new_position) / new_scale;
+            assert!(
+                new_offset.abs_diff_eq(cursor_offset, 1e-3),
+                "cursor moved to {} when zooming from {} to {}",
+                new_offset,
+                old_scale,
+                new_scale
+            );
+        }
+    }
+
+    #[test]
+    fn without_bounds_anything_goes() {
+        let position = Vec2::new(1e6, -1e6);
+        assert_eq!(
+            CameraBounds::default().clamp(position, 1.0, Vec2::ONE),
+            position
+        );
+    }
+}
@@ -0,0 +1,280 @@
+use crate::localization::MessageId;
+use crate::simulation::{
+    simulation_running, SimulationPlugin, SimulationStage, SIMULATION_TIMESTEP,
+};
+use crate::transport::{ship_movement_system, ShipArrived};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+
+/// Planets stop producing a good when they have this much of it in stock.
+const MAX_PLANET_STOCK: f32 = 100.0;
+
+/// Amount of goods a ship can carry.
+pub const DEFAULT_CARGO_CAPACITY: f32 = 20.0;
+
+pub struct EconomyPlugin;
+
+impl Plugin for EconomyPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<SimulationPlugin>() {
+            app.add_plugin(SimulationPlugin);
+        }
+
+        // Also added by the transport plugin, but the economy can run without ships.
+        app.add_event::<ShipArrived>().add_system_set_to_stage(
+            SimulationStage,
+            SystemSet::new()
+                .with_run_criteria(simulation_running)
+                // All of these change the stock of planets, so they always run in the same order
+                // to keep the simulation deterministic.
+                .with_system(production_system.before(consumption_system))
+                .with_system(consumption_system)
+                .with_system(
+                    cargo_transfer_system
+                        .after(consumption_system)
+                        .after(ship_movement_system),
+                ),
+        );
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub enum Good {
+    Food,
+    Ore,
+    Fuel,
+}
+
+impl Good {
+    pub const ALL: [Good; 3] = [Good::Food, Good::Ore, Good::Fuel];
+
+    /// Id of the localization message with the name of this good.
+    pub fn message_id(&self) -> MessageId {
+        match self {
+            Good::Food => MessageId::GoodFood,
+            Good::Ore => MessageId::GoodOre,
+            Good::Fuel => MessageId::GoodFuel,
+        }
+    }
+}
+
+/// Goods stored on a planet, or carried by a ship.
+#[derive(Component, Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Inventory {
+    goods: HashMap<Good, f32>,
+}
+
+impl Inventory {
+    pub fn amount(&self, good: Good) -> f32 {
+        self.goods.get(&good).copied().unwrap_or(0.0)
+    }
+
+    pub fn total(&self) -> f32 {
+        self.goods.values().sum()
+    }
+
+    pub fn add(&mut self, good: Good, amount: f32) {
+        *self.goods.entry(good).or_insert(0.0) += amount;
+    }
+
+    /// Takes at most `max_amount` of the good out of the inventory.
+    /// Returns how much was actually taken.
+    pub fn take(&mut self, good: Good, max_amount: f32) -> f32 {
+        let Some(stock) = self.goods.get_mut(&good) else { return 0.0; };
+
+        let taken = stock.min(max_amount).max(0.0);
+        *stock -= taken;
+        taken
+    }
+}
+
+/// Goods a planet produces, in units per second.
+#[derive(Component, Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Production {
+    pub rates: HashMap<Good, f32>,
+}
+
+/// Goods a planet consumes, in units per second.
+#[derive(Component, Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Demand {
+    pub rates: HashMap<Good, f32>,
+}
+
+/// The cargo hold of a ship.
+#[derive(Component, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Cargo {
+    pub capacity: f32,
+    pub goods: Inventory,
+}
+
+impl Cargo {
+    pub fn new(capacity: f32) -> Self {
+        Cargo {
+            capacity,
+            goods: Inventory::default(),
+        }
+    }
+
+    pub fn free_space(&self) -> f32 {
+        (self.capacity - self.goods.total()).max(0.0)
+    }
+}
+
+fn production_system(mut producers: Query<(&Production, &mut Inventory)>) {
+    for (production, mut inventory) in producers.iter_mut() {
+        for (good, rate) in production.rates.iter() {
+            let space_left = (MAX_PLANET_STOCK - inventory.amount(*good)).max(0.0);
+            inventory.add(*good, (rate * SIMULATION_TIMESTEP).min(space_left));
+        }
+    }
+}
+
+fn consumption_system(mut consumers: Query<(&Demand, &mut Inventory)>) {
+    for (demand, mut inventory) in consumers.iter_mut() {
+        for (good, rate) in demand.rates.iter() {
+            inventory.take(*good, rate * SIMULATION_TIMESTEP);
+        }
+    }
+}
+
+/// When a ship arrives at a planet, it unloads all the goods that the planet demands,
+/// and then fills up its cargo hold with the goods that the planet produces.
+fn cargo_transfer_system(
+    mut arrivals: EventReader<ShipArrived>,
+    mut ships: Query<&mut Cargo>,
+    mut planets: Query<(&mut Inventory, Option<&Production>, Option<&Demand>)>,
+) {
+    for arrival in arrivals.iter() {
+        let Ok(mut cargo) = ships.get_mut(arrival.ship) else { continue; };
+        let Ok((mut inventory, production, demand)) = planets.get_mut(arrival.planet) else { continue; };
+
+        if let Some(demand) = demand {
+            for good in demand.rates.keys() {
+                let unloaded = cargo.goods.take(*good, f32::INFINITY);
+                inventory.add(*good, unloaded);
+            }
+        }
+
+        if let Some(production) = production {
+            for good in production.rates.keys() {
+                let loaded = inventory.take(*good, cargo.free_space());
+                cargo.goods.add(*good, loaded);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::testing::{headless_app, run_ticks};
+
+    fn economy_app() -> App {
+        let mut app = headless_app();
+        app.add_plugin(EconomyPlugin);
+        app
+    }
+
+    fn rates(good: Good, rate: f32) -> HashMap<Good, f32> {
+        let mut rates = HashMap::default();
+        rates.insert(good, rate);
+        rates
+    }
+
+    fn inventory(good: Good, amount: f32) -> Inventory {
+        let mut inventory = Inventory::default();
+        inventory.add(good, amount);
+        inventory
+    }
+
+    fn assert_amount(inventory: &Inventory, good: Good, expected: f32) {
+        let amount = inventory.amount(good);
+        assert!(
+            (amount - expected).abs() < 1e-3,
+            "expected {} of {:?}, found {}",
+            expected,
+            good,
+            amount
+        );
+    }
+
+    #[test]
+    fn production_stops_at_max_stock() {
+        let mut app = economy_app();
+        let planet = app
+            .world
+            .spawn((
+                inventory(Good::Food, MAX_PLANET_STOCK - 2.0),
+                Production {
+                    rates: rates(Good::Food, 30.0),
+                },
+            ))
+            .id();
+
+        run_ticks(&mut app, 1);
+        assert_amount(
+            app.world.get(planet).unwrap(),
+            Good::Food,
+            MAX_PLANET_STOCK - 1.0,
+        );
+
+        run_ticks(&mut app, 10);
+        assert_amount(app.world.get(planet).unwrap(), Good::Food, MAX_PLANET_STOCK);
+    }
+
+    #[test]
+    fn consumption_never_goes_below_zero() {
+        let mut app = economy_app();
+        let planet = app
+            .world
+            .spawn((
+                inventory(Good::Ore, 1.0),
+                Demand {
+                    rates: rates(Good::Ore, 3.0),
+                },
+            ))
+            .id();
+
+        run_ticks(&mut app, 3);
+        assert_amount(app.world.get(planet).unwrap(), Good::Ore, 0.7);
+
+        run_ticks(&mut app, 30);
+        assert_amount(app.world.get(planet).unwrap(), Good::Ore, 0.0);
+    }
+
+    #[test]
+    fn arriving_ship_unloads_before_loading() {
+        let mut app = economy_app();
+        // Rates of zero, so that only the cargo transfer changes the stock.
+        let mut stock = inventory(Good::Ore, 50.0);
+        stock.add(Good::Food, 5.0);
+        let planet = app
+            .world
+            .spawn((
+                stock,
+                Production {
+                    rates: rates(Good::Ore, 0.0),
+                },
+                Demand {
+                    rates: rates(Good::Food, 0.0),
+                },
+            ))
+            .id();
+        let mut cargo = Cargo::new(DEFAULT_CARGO_CAPACITY);
+        cargo.goods.add(Good::Food, DEFAULT_CARGO_CAPACITY);
+        let ship = app.world.spawn(cargo).id();
+
+        app.world.send_event(ShipArrived { ship, planet });
+        run_ticks(&mut app, 1);
+
+        // The full hold only has room for the ore because the food was unloaded first.
+        let cargo = app.world.get::<Cargo>(ship).unwrap();
+        assert_amount(&cargo.goods, Good::Food, 0.0);
+        assert_amount(&cargo.goods, Good::Ore, DEFAULT_CARGO_CAPACITY);
+
+        let stock = app.world.get::<Inventory>(planet).unwrap();
+        assert_amount(stock, Good::Food, 5.0 + DEFAULT_CARGO_CAPACITY);
+        assert_amount(stock, Good::Ore, 50.0 - DEFAULT_CARGO_CAPACITY);
+    }
+}
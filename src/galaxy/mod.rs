@@ -0,0 +1,340 @@
+mod names;
+
+use crate::economy::{Demand, Good, Production};
+use crate::galaxy::names::generate_planet_name;
+use crate::save::PendingLoad;
+use crate::{spawn_planet, GameState, Planet};
+use bevy::asset::{AssetLoader, BoxedFuture, LoadContext, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::utils::HashSet;
+use bevy_asset_loader::prelude::*;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::Deserialize;
+use std::f32::consts::TAU;
+use thiserror::Error;
+
+/// How many times we try to find a free spot for a planet, before giving up on it.
+const MAX_PLACEMENT_ATTEMPTS: usize = 100;
+/// How many times we try to generate a name that isn't taken yet, before numbering the planet.
+const MAX_NAME_ATTEMPTS: usize = 100;
+
+pub struct GalaxyPlugin;
+
+impl Plugin for GalaxyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<GalaxyConfig>()
+            .init_asset_loader::<GalaxyConfigLoader>()
+            .add_system_set(SystemSet::on_enter(GameState::Main).with_system(spawn_galaxy));
+    }
+}
+
+#[derive(AssetCollection, Resource)]
+pub struct GalaxyAssets {
+    #[asset(key = "galaxy_config")]
+    pub(crate) config: Handle<GalaxyConfig>,
+}
+
+/// `*.galaxy.ron` file with the settings for the procedural galaxy generator.
+/// The same config always generates the same galaxy.
+#[derive(Deserialize, TypeUuid, Clone, Debug)]
+#[uuid = "b0b8ab20-a5e1-4c7c-b8a9-d4f63d407442"]
+pub struct GalaxyConfig {
+    pub seed: u64,
+    pub planet_count: usize,
+    /// All planets are placed within this distance of the center of the galaxy.
+    pub radius: f32,
+    /// Minimum distance between the edges of two planets.
+    pub min_planet_spacing: f32,
+    pub min_planet_radius: f32,
+    pub max_planet_radius: f32,
+    /// Goods are produced and consumed at a rate between `0` and this, in units per second.
+    pub max_good_rate: f32,
+}
+
+impl GalaxyConfig {
+    /// Checks that the generator can work with the config. Without this, a broken config would
+    /// crash the game as soon as a new game starts, instead of when it is loaded.
+    pub fn validate(&self) -> Result<(), GalaxyConfigError> {
+        let lengths = [
+            ("radius", self.radius),
+            ("min_planet_spacing", self.min_planet_spacing),
+            ("min_planet_radius", self.min_planet_radius),
+            ("max_planet_radius", self.max_planet_radius),
+            ("max_good_rate", self.max_good_rate),
+        ];
+        for (field, value) in lengths {
+            if !value.is_finite() || value < 0.0 {
+                return Err(GalaxyConfigError::Negative { field, value });
+            }
+        }
+
+        if self.min_planet_radius == 0.0 {
+            return Err(GalaxyConfigError::ZeroPlanetRadius);
+        }
+        if self.min_planet_radius > self.max_planet_radius {
+            return Err(GalaxyConfigError::PlanetRadiusRange {
+                min: self.min_planet_radius,
+                max: self.max_planet_radius,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Everything needed to spawn a single planet.
+#[derive(Clone, PartialEq, Debug)]
+pub struct GeneratedPlanet {
+    pub name: String,
+    pub position: Vec2,
+    pub radius: f32,
+    pub produces: Good,
+    pub production_rate: f32,
+    pub demands: Good,
+    pub demand_rate: f32,
+}
+
+/// Scatters planets over a disk, using the seed in the config.
+/// Returns fewer planets than requested if the galaxy is too crowded to fit them all.
+pub fn generate_galaxy(config: &GalaxyConfig) -> Vec<GeneratedPlanet> {
+    let mut rng = ChaCha8Rng::seed_from_u64(config.seed);
+    let mut planets: Vec<GeneratedPlanet> = Vec::with_capacity(config.planet_count);
+    let mut used_names = HashSet::default();
+
+    for _ in 0..config.planet_count {
+        let radius = rng.gen_range(config.min_planet_radius..=config.max_planet_radius);
+
+        let position = (0..MAX_PLACEMENT_ATTEMPTS)
+            .map(|_| random_point_in_disk(&mut rng, config.radius))
+            .find(|position| {
+                planets.iter().all(|other| {
+                    position.distance(other.position)
+                        >= radius + other.radius + config.min_planet_spacing
+                })
+            });
+        let Some(position) = position else {
+            warn!(
+                "Could not find a free spot for planet {} of {}, the galaxy is too crowded",
+                planets.len() + 1,
+                config.planet_count
+            );
+            break;
+        };
+
+        let mut name = generate_planet_name(&mut rng);
+        for _ in 0..MAX_NAME_ATTEMPTS {
+            if !used_names.contains(&name) {
+                break;
+            }
+            name = generate_planet_name(&mut rng);
+        }
+        // The generator ran out of fresh names, so number the duplicates instead.
+        let base_name = name.clone();
+        let mut number = 2;
+        while used_names.contains(&name) {
+            name = format!("{} {}", base_name, number);
+            number += 1;
+        }
+        used_names.insert(name.clone());
+
+        let mut goods = Good::ALL;
+        goods.shuffle(&mut rng);
+
+        planets.push(GeneratedPlanet {
+            name,
+            position,
+            radius,
+            produces: goods[0],
+            production_rate: rng.gen_range(0.0..=config.max_good_rate),
+            demands: goods[1],
+            demand_rate: rng.gen_range(0.0..=config.max_good_rate),
+        });
+    }
+
+    planets
+}
+
+/// Uniformly distributed point within a circle around the origin.
+fn random_point_in_disk(rng: &mut impl Rng, radius: f32) -> Vec2 {
+    // Without the square root, points would bunch up around the center.
+    let distance = radius * rng.gen::<f32>().sqrt();
+    let angle = rng.gen_range(0.0..TAU);
+    Vec2::new(angle.cos(), angle.sin()) * distance
+}
+
+/// Generates a new galaxy when starting a new game.
+/// When a save game is about to be loaded, there is no need for a new galaxy.
+pub fn spawn_galaxy(
+    mut commands: Commands,
+    galaxy_assets: Res<GalaxyAssets>,
+    configs: Res<Assets<GalaxyConfig>>,
+    pending_load: Option<Res<PendingLoad>>,
+) {
+    if pending_load.is_some() {
+        return;
+    }
+
+    let Some(config) = configs.get(&galaxy_assets.config) else {
+        error!("Galaxy config not loaded, cannot generate a galaxy");
+        return;
+    };
+
+    for planet in generate_galaxy(config) {
+        spawn_planet(
+            &mut commands,
+            Planet {
+                name: planet.name,
+                radius: planet.radius,
+            },
+            planet.position,
+            Production {
+                rates: [(planet.produces, planet.production_rate)]
+                    .into_iter()
+                    .collect(),
+            },
+            Demand {
+                rates: [(planet.demands, planet.demand_rate)].into_iter().collect(),
+            },
+        );
+    }
+}
+
+#[derive(Default)]
+pub struct GalaxyConfigLoader;
+
+impl AssetLoader for GalaxyConfigLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let config = ron::de::from_bytes::<GalaxyConfig>(bytes)?;
+            config.validate()?;
+            load_context.set_default_asset(LoadedAsset::new(config));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["galaxy.ron"]
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum GalaxyConfigError {
+    #[error("Galaxy config field `{field}` must be 0 or more, but is {value}")]
+    Negative { field: &'static str, value: f32 },
+
+    #[error("Galaxy config field `min_planet_radius` must be bigger than 0")]
+    ZeroPlanetRadius,
+
+    #[error(
+        "Galaxy config `min_planet_radius` ({min}) is bigger than `max_planet_radius` ({max})"
+    )]
+    PlanetRadiusRange { min: f32, max: f32 },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config() -> GalaxyConfig {
+        GalaxyConfig {
+            seed: 42,
+            planet_count: 60,
+            radius: 1500.0,
+            min_planet_spacing: 40.0,
+            min_planet_radius: 10.0,
+            max_planet_radius: 30.0,
+            max_good_rate: 2.0,
+        }
+    }
+
+    #[test]
+    fn same_seed_generates_same_galaxy() {
+        let config = test_config();
+        assert_eq!(generate_galaxy(&config), generate_galaxy(&config));
+
+        let other_seed = GalaxyConfig {
+            seed: 43,
+            ..test_config()
+        };
+        assert_ne!(generate_galaxy(&config), generate_galaxy(&other_seed));
+    }
+
+    #[test]
+    fn planets_keep_their_distance() {
+        let config = test_config();
+        let planets = generate_galaxy(&config);
+        assert_eq!(planets.len(), config.planet_count);
+
+        for (index, planet) in planets.iter().enumerate() {
+            assert!(planet.position.length() <= config.radius);
+            for other in &planets[index + 1..] {
+                let gap = planet.position.distance(other.position) - planet.radius - other.radius;
+                assert!(
+                    gap >= config.min_planet_spacing,
+                    "{} and {} are only {} apart",
+                    planet.name,
+                    other.name,
+                    gap
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn planet_names_are_unique() {
+        // More planets than the name generator comfortably has names for.
+        let config = GalaxyConfig {
+            planet_count: 3000,
+            radius: 100_000.0,
+            ..test_config()
+        };
+        let planets = generate_galaxy(&config);
+        let names = planets
+            .iter()
+            .map(|planet| &planet.name)
+            .collect::<HashSet<_>>();
+        assert_eq!(names.len(), planets.len());
+    }
+
+    #[test]
+    fn broken_configs_are_rejected() {
+        assert!(test_config().validate().is_ok());
+
+        let swapped_radii = GalaxyConfig {
+            min_planet_radius: 30.0,
+            max_planet_radius: 10.0,
+            ..test_config()
+        };
+        assert!(matches!(
+            swapped_radii.validate(),
+            Err(GalaxyConfigError::PlanetRadiusRange { .. })
+        ));
+
+        let negative_rate = GalaxyConfig {
+            max_good_rate: -1.0,
+            ..test_config()
+        };
+        assert!(matches!(
+            negative_rate.validate(),
+            Err(GalaxyConfigError::Negative {
+                field: "max_good_rate",
+                ..
+            })
+        ));
+
+        let zero_radius = GalaxyConfig {
+            min_planet_radius: 0.0,
+            ..test_config()
+        };
+        assert!(matches!(
+            zero_radius.validate(),
+            Err(GalaxyConfigError::ZeroPlanetRadius)
+        ));
+    }
+}
@@ -0,0 +1,37 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+const PREFIXES: &[&str] = &[
+    "Al", "Bel", "Cor", "Dra", "El", "Fen", "Gal", "Hal", "Ix", "Jor", "Kep", "Lum", "Mor", "Nor",
+    "Or", "Pra", "Quel", "Ris", "Sol", "Tar", "Ul", "Vex", "Wyn", "Xan", "Yor", "Zeph",
+];
+
+const MIDDLES: &[&str] = &["a", "e", "i", "o", "u", "ar", "en", "is", "on", "ul"];
+
+const SUFFIXES: &[&str] = &[
+    "bis", "ceti", "dor", "gon", "lia", "mus", "nix", "ra", "rion", "tis", "tar", "via", "x",
+];
+
+const DESIGNATIONS: &[&str] = &["Prime", "Minor", "Major", "II", "III", "IV", "V"];
+
+/// Chance that a name gets a designation (like "Prime" or "IV") at the end.
+const DESIGNATION_CHANCE: f64 = 0.2;
+
+/// Generates a planet name by gluing syllables together.
+/// The same random number generator state always produces the same name.
+pub fn generate_planet_name(rng: &mut impl Rng) -> String {
+    let mut name = String::new();
+
+    name.push_str(PREFIXES.choose(rng).unwrap());
+    if rng.gen_bool(0.5) {
+        name.push_str(MIDDLES.choose(rng).unwrap());
+    }
+    name.push_str(SUFFIXES.choose(rng).unwrap());
+
+    if rng.gen_bool(DESIGNATION_CHANCE) {
+        name.push(' ');
+        name.push_str(DESIGNATIONS.choose(rng).unwrap());
+    }
+
+    name
+}
@@ -0,0 +1,55 @@
+use crate::input::{Action, Controls, VirtualCursor};
+use bevy::prelude::*;
+use bevy_egui::EguiContext;
+use leafwing_input_manager::prelude::*;
+
+/// The input devices that the UI is using at the moment. While the UI uses a device, the
+/// bindings of world actions on that device are left out of the [InputMap], so that for example
+/// clicking a button doesn't also deselect the planet behind it.
+/// See [crate::input::Action::is_world_action].
+#[derive(Resource, Default, PartialEq, Eq, Clone, Copy, Debug)]
+pub struct UiInputClaims {
+    /// The mouse is hovering over, or dragging, something in the UI.
+    pub pointer: bool,
+    /// A text field in the UI has keyboard focus.
+    pub keyboard: bool,
+    /// The virtual cursor is over the UI, so the gamepad buttons are pressing UI buttons.
+    /// The sticks stay with the world actions, so that the cursor can still be moved away.
+    pub gamepad_buttons: bool,
+}
+
+/// Asks egui which devices it wants. Egui bases this on the previous frame, which is the one
+/// the player was looking at when they pressed a button.
+pub fn ui_input_claims_system(
+    mut egui_context: ResMut<EguiContext>,
+    windows: Res<Windows>,
+    virtual_cursor: Res<VirtualCursor>,
+    mut claims: ResMut<UiInputClaims>,
+) {
+    let context = egui_context.ctx_mut();
+    let virtual_cursor_over_ui = windows
+        .get_primary()
+        .and_then(|window| virtual_cursor.egui_position(window))
+        .map_or(false, |position| context.layer_id_at(position).is_some());
+    let current_claims = UiInputClaims {
+        pointer: context.wants_pointer_input() || virtual_cursor_over_ui,
+        keyboard: context.wants_keyboard_input(),
+        gamepad_buttons: virtual_cursor_over_ui,
+    };
+
+    // Only write when something changed, so that the input map isn't rebuilt every frame.
+    if *claims != current_claims {
+        *claims = current_claims;
+    }
+}
+
+/// Rebuilds the [InputMap] when the controls change, or when the UI claims or releases a device.
+pub fn input_map_system(
+    controls: Res<Controls>,
+    claims: Res<UiInputClaims>,
+    mut input_map: ResMut<InputMap<Action>>,
+) {
+    if controls.is_changed() || claims.is_changed() {
+        *input_map = controls.input_map(*claims);
+    }
+}
@@ -0,0 +1,397 @@
+use crate::camera::BOOKMARK_SLOTS;
+use crate::input::{Action, UiInputClaims};
+use crate::ron_file::{read_ron_file, save_ron_file, RonFileError};
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+use leafwing_input_manager::user_input::InputKind;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+const CONTROLS_PATH: &str = "controls.ron";
+
+/// Keys of the camera bookmark slots, by slot.
+const BOOKMARK_KEYS: [KeyCode; BOOKMARK_SLOTS] = [
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+];
+
+/// Keys that count as modifiers in chords, like the `Shift` in `Shift + H`.
+const MODIFIER_KEYS: [(Modifier, [KeyCode; 2]); 4] = [
+    (Modifier::Alt, [KeyCode::LAlt, KeyCode::RAlt]),
+    (Modifier::Control, [KeyCode::LControl, KeyCode::RControl]),
+    (Modifier::Shift, [KeyCode::LShift, KeyCode::RShift]),
+    (Modifier::Win, [KeyCode::LWin, KeyCode::RWin]),
+];
+
+/// One way to trigger an action: a single input, or several inputs that have to be pressed together.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Binding {
+    pub inputs: Vec<InputKind>,
+}
+
+impl Binding {
+    pub fn new(inputs: impl IntoIterator<Item = impl Into<InputKind>>) -> Self {
+        Binding {
+            inputs: inputs.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Whether any of the inputs is on the mouse.
+    fn uses_pointer(&self) -> bool {
+        self.inputs
+            .iter()
+            .any(|input| matches!(input, InputKind::Mouse(_) | InputKind::MouseWheel(_)))
+    }
+
+    /// Whether any of the inputs is on the keyboard.
+    fn uses_keyboard(&self) -> bool {
+        self.inputs
+            .iter()
+            .any(|input| matches!(input, InputKind::Keyboard(_) | InputKind::Modifier(_)))
+    }
+
+    /// Whether any of the inputs is a gamepad button.
+    fn uses_gamepad_buttons(&self) -> bool {
+        self.inputs
+            .iter()
+            .any(|input| matches!(input, InputKind::GamepadButton(_)))
+    }
+
+    /// Whether both bindings are triggered by the same inputs, regardless of their order.
+    fn same_inputs_as(&self, other: &Binding) -> bool {
+        self.inputs.len() == other.inputs.len()
+            && self.inputs.iter().all(|input| other.inputs.contains(input))
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inputs = self
+            .inputs
+            .iter()
+            .map(|input| match input {
+                InputKind::Keyboard(key) => format!("{:?}", key),
+                InputKind::Modifier(modifier) => format!("{:?}", modifier),
+                InputKind::Mouse(button) => format!("Mouse {:?}", button),
+                InputKind::MouseWheel(direction) => format!("Wheel {:?}", direction),
+                InputKind::GamepadButton(button) => format!("Gamepad {:?}", button),
+                other => format!("{:?}", other),
+            })
+            .collect::<Vec<_>>();
+        write!(f, "{}", inputs.join(" + "))
+    }
+}
+
+/// The player's key bindings, from which the [InputMap] is built.
+/// Kept between sessions in a file of its own, so it can easily be shared.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+pub struct Controls {
+    bindings: BTreeMap<Action, Vec<Binding>>,
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        let mut bindings = BTreeMap::new();
+        let mut bind = |action, binding| {
+            bindings
+                .entry(action)
+                .or_insert_with(Vec::new)
+                .push(binding);
+        };
+
+        bind(Action::HelloAction, Binding::new([KeyCode::Space]));
+        bind(
+            Action::HelloAction,
+            Binding::new([
+                InputKind::Modifier(Modifier::Shift),
+                InputKind::Keyboard(KeyCode::H),
+            ]),
+        );
+        bind(
+            Action::PrimaryInteraction,
+            Binding::new([MouseButton::Left]),
+        );
+        bind(
+            Action::PrimaryInteraction,
+            Binding::new([GamepadButtonType::South]),
+        );
+        bind(Action::CursorMove, Binding::new([DualAxis::right_stick()]));
+        bind(Action::Pause, Binding::new([KeyCode::Escape]));
+        bind(Action::Pause, Binding::new([GamepadButtonType::Start]));
+        bind(Action::QuickSave, Binding::new([KeyCode::F5]));
+        bind(Action::QuickLoad, Binding::new([KeyCode::F9]));
+        bind(Action::CameraUp, Binding::new([KeyCode::W]));
+        bind(Action::CameraDown, Binding::new([KeyCode::S]));
+        bind(Action::CameraLeft, Binding::new([KeyCode::A]));
+        bind(Action::CameraRight, Binding::new([KeyCode::D]));
+        bind(
+            Action::CameraZoomIn,
+            Binding::new([MouseWheelDirection::Up]),
+        );
+        bind(
+            Action::CameraZoomOut,
+            Binding::new([MouseWheelDirection::Down]),
+        );
+
+        bind(Action::CameraPan, Binding::new([DualAxis::left_stick()]));
+        bind(
+            Action::CameraZoomInHold,
+            Binding::new([GamepadButtonType::RightTrigger2]),
+        );
+        bind(
+            Action::CameraZoomOutHold,
+            Binding::new([GamepadButtonType::LeftTrigger2]),
+        );
+        bind(Action::CameraDrag, Binding::new([MouseButton::Middle]));
+        bind(
+            Action::CameraToggleEdgeScrolling,
+            Binding::new([KeyCode::F2]),
+        );
+        bind(Action::CameraFocusSelected, Binding::new([KeyCode::F]));
+        bind(Action::CameraFollow, Binding::new([KeyCode::T]));
+        for (slot, key) in BOOKMARK_KEYS.into_iter().enumerate() {
+            bind(
+                Action::STORE_BOOKMARK[slot],
+                Binding::new([
+                    InputKind::Modifier(Modifier::Control),
+                    InputKind::Keyboard(key),
+                ]),
+            );
+            bind(Action::RECALL_BOOKMARK[slot], Binding::new([key]));
+        }
+
+        Controls { bindings }
+    }
+}
+
+impl Controls {
+    pub fn bindings(&self, action: Action) -> &[Binding] {
+        self.bindings
+            .get(&action)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Replaces the binding at `index`, or adds a new binding if `index` is `None`.
+    pub fn set_binding(&mut self, action: Action, index: Option<usize>, binding: Binding) {
+        let bindings = self.bindings.entry(action).or_default();
+        match index.and_then(|index| bindings.get_mut(index)) {
+            Some(existing) => *existing = binding,
+            None => bindings.push(binding),
+        }
+    }
+
+    pub fn remove_binding(&mut self, action: Action, index: usize) {
+        if let Some(bindings) = self.bindings.get_mut(&action) {
+            if index < bindings.len() {
+                bindings.remove(index);
+            }
+        }
+    }
+
+    /// The other actions that are triggered by the same inputs as the given binding.
+    pub fn conflicts(&self, action: Action, binding: &Binding) -> Vec<Action> {
+        self.bindings
+            .iter()
+            .filter(|(other_action, other_bindings)| {
+                **other_action != action
+                    && other_bindings
+                        .iter()
+                        .any(|other| other.same_inputs_as(binding))
+            })
+            .map(|(other_action, _)| *other_action)
+            .collect()
+    }
+
+    /// Builds the input map, without the bindings of world actions on devices that the UI claims.
+    pub fn input_map(&self, claims: UiInputClaims) -> InputMap<Action> {
+        let mut input_map = InputMap::default();
+        for (action, bindings) in self.bindings.iter() {
+            for binding in bindings {
+                let claimed_by_ui = (claims.pointer && binding.uses_pointer())
+                    || (claims.keyboard && binding.uses_keyboard())
+                    || (claims.gamepad_buttons && binding.uses_gamepad_buttons());
+                if claimed_by_ui && action.is_world_action() {
+                    continue;
+                }
+
+                match binding.inputs.as_slice() {
+                    [] => {}
+                    [input] => {
+                        input_map.insert(*input, *action);
+                    }
+                    inputs => {
+                        input_map.insert_chord(inputs.iter().copied(), *action);
+                    }
+                }
+            }
+        }
+        input_map
+    }
+
+    /// Returns the default controls if there is no controls file yet.
+    pub fn read_from_file() -> Result<Controls, RonFileError> {
+        let controls = read_ron_file::<Controls>(CONTROLS_PATH)?;
+        Ok(controls.map(Controls::with_new_actions).unwrap_or_default())
+    }
+
+    /// Gives actions that were added to the game after the controls were saved their default bindings.
+    fn with_new_actions(mut self) -> Controls {
+        for (action, bindings) in Controls::default().bindings {
+            self.bindings.entry(action).or_insert(bindings);
+        }
+        self
+    }
+
+    /// Writes the controls to disk. Failing to do so is not fatal, so errors are only logged.
+    pub fn save(&self) {
+        save_ron_file(CONTROLS_PATH, self, "controls");
+    }
+}
+
+/// Returns the binding the player is entering, as soon as they press a key, mouse button or
+/// gamepad button (or turn the mouse wheel), together with the modifier keys they are holding.
+/// Sticks can't be captured this way, because they are too easily moved by accident.
+/// Modifier keys alone don't finish a binding, because they might be the start of a chord.
+pub fn capture_binding(
+    keys: &Input<KeyCode>,
+    mouse_buttons: &Input<MouseButton>,
+    gamepad_buttons: &Input<GamepadButton>,
+    mouse_wheel_events: &mut EventReader<'_, '_, MouseWheel>,
+    capture_mouse: bool,
+) -> Option<Binding> {
+    let wheel_movement: f32 = mouse_wheel_events.iter().map(|event| event.y).sum();
+
+    let key = keys
+        .get_just_pressed()
+        .find(|key| !MODIFIER_KEYS.iter().any(|(_, keys)| keys.contains(key)))
+        .map(|key| InputKind::Keyboard(*key));
+    let gamepad_button = gamepad_buttons
+        .get_just_pressed()
+        .next()
+        .map(|button| InputKind::GamepadButton(button.button_type));
+    let mouse_button = mouse_buttons
+        .get_just_pressed()
+        .next()
+        .map(|button| InputKind::Mouse(*button));
+    let mouse_wheel = if wheel_movement > 0.0 {
+        Some(InputKind::MouseWheel(MouseWheelDirection::Up))
+    } else if wheel_movement < 0.0 {
+        Some(InputKind::MouseWheel(MouseWheelDirection::Down))
+    } else {
+        None
+    };
+
+    let input = key.or(gamepad_button).or_else(|| {
+        if capture_mouse {
+            mouse_button.or(mouse_wheel)
+        } else {
+            None
+        }
+    })?;
+
+    let mut inputs = MODIFIER_KEYS
+        .iter()
+        .filter(|(_, modifier_keys)| keys.any_pressed(*modifier_keys))
+        .map(|(modifier, _)| InputKind::Modifier(*modifier))
+        .collect::<Vec<_>>();
+    inputs.push(input);
+    Some(Binding { inputs })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ron_file::{from_ron, to_ron};
+
+    #[test]
+    fn same_inputs_in_any_order_conflict() {
+        let controls = Controls::default();
+
+        assert_eq!(
+            controls.conflicts(Action::CameraFollow, &Binding::new([KeyCode::W])),
+            [Action::CameraUp]
+        );
+        let shift_h = Binding::new([
+            InputKind::Keyboard(KeyCode::H),
+            InputKind::Modifier(Modifier::Shift),
+        ]);
+        assert_eq!(
+            controls.conflicts(Action::CameraFollow, &shift_h),
+            [Action::HelloAction]
+        );
+        assert!(controls
+            .conflicts(Action::CameraUp, &Binding::new([KeyCode::W]))
+            .is_empty());
+        // Part of a chord isn't the same as the whole chord.
+        assert!(controls
+            .conflicts(Action::CameraFollow, &Binding::new([KeyCode::H]))
+            .is_empty());
+    }
+
+    #[test]
+    fn claimed_devices_are_only_taken_from_world_actions() {
+        let controls = Controls::default();
+
+        let input_map = controls.input_map(UiInputClaims {
+            pointer: true,
+            ..default()
+        });
+        // The gamepad button is left, the mouse button is taken.
+        assert_eq!(input_map.get(Action::PrimaryInteraction).len(), 1);
+        assert_eq!(input_map.get(Action::CameraUp).len(), 1);
+
+        let input_map = controls.input_map(UiInputClaims {
+            keyboard: true,
+            ..default()
+        });
+        assert_eq!(input_map.get(Action::PrimaryInteraction).len(), 2);
+        assert_eq!(input_map.get(Action::CameraUp).len(), 0);
+        // Both the key and the chord with a modifier are keyboard bindings.
+        assert_eq!(input_map.get(Action::HelloAction).len(), 0);
+        // The menu can still be opened while typing in a text field.
+        assert_eq!(input_map.get(Action::Pause).len(), 2);
+        assert_eq!(input_map.get(Action::QuickSave).len(), 1);
+
+        let input_map = controls.input_map(UiInputClaims {
+            gamepad_buttons: true,
+            ..default()
+        });
+        assert_eq!(input_map.get(Action::PrimaryInteraction).len(), 1);
+        // The virtual cursor can still be moved off the UI.
+        assert_eq!(input_map.get(Action::CursorMove).len(), 1);
+        assert_eq!(input_map.get(Action::Pause).len(), 2);
+    }
+
+    #[test]
+    fn older_controls_get_bindings_for_new_actions() {
+        let older_controls = Controls {
+            bindings: BTreeMap::from([(Action::Pause, vec![Binding::new([KeyCode::P])])]),
+        };
+        let ron_string = to_ron(&older_controls).unwrap();
+
+        let controls = from_ron::<Controls>(&ron_string)
+            .unwrap()
+            .with_new_actions();
+
+        assert_eq!(
+            controls.bindings(Action::Pause),
+            [Binding::new([KeyCode::P])]
+        );
+        assert_eq!(
+            controls.bindings(Action::CameraUp),
+            Controls::default().bindings(Action::CameraUp)
+        );
+        assert_eq!(controls.bindings.len(), Controls::default().bindings.len());
+    }
+}
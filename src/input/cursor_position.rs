@@ -1,4 +1,5 @@
 use crate::camera::MainCamera;
+use crate::input::VirtualCursor;
 use bevy::math::{Vec2, Vec3Swizzles};
 use bevy::prelude::*;
 
@@ -8,11 +9,15 @@ pub struct GlobalCursorPosition(Vec2);
 pub fn update_global_cursor_position(
     camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
     windows: Res<Windows>,
+    virtual_cursor: Res<VirtualCursor>,
     mut global_cursor_position: ResMut<GlobalCursorPosition>,
 ) {
     let Ok((camera, camera_transform)) = camera_query.get_single() else { return; };
     let Some(primary_window) = windows.get_primary() else { return; };
-    let Some(viewport_position) = primary_window.cursor_position() else { return; };
+    let viewport_position = virtual_cursor
+        .position()
+        .or_else(|| primary_window.cursor_position());
+    let Some(viewport_position) = viewport_position else { return; };
 
     let Some(ray) = camera.viewport_to_world(camera_transform, viewport_position) else { return; };
 
@@ -1,11 +1,26 @@
+mod arbitration;
+mod controls;
 mod cursor_position;
+mod virtual_cursor;
 
-use crate::input::cursor_position::update_global_cursor_position;
-pub use crate::input::cursor_position::GlobalCursorPosition;
+use crate::camera::BOOKMARK_SLOTS;
+use crate::input::arbitration::{input_map_system, ui_input_claims_system};
+use crate::input::virtual_cursor::{
+    virtual_cursor_egui_input_system, virtual_cursor_system, virtual_cursor_ui,
+};
+use crate::localization::{FluentArgs, MessageId};
 use bevy::app::{App, Plugin};
-use bevy::prelude::{Commands, KeyCode, MouseButton, Res};
+use bevy::input::InputSystem;
+use bevy::prelude::{warn, Commands, CoreStage, IntoSystemDescriptor, Res};
+use bevy_egui::EguiSystem;
+use leafwing_input_manager::plugin::InputManagerSystem;
 use leafwing_input_manager::prelude::*;
-use leafwing_input_manager::user_input::InputKind;
+use serde::{Deserialize, Serialize};
+
+pub use crate::input::arbitration::UiInputClaims;
+pub use crate::input::controls::{capture_binding, Binding, Controls};
+pub use crate::input::cursor_position::{update_global_cursor_position, GlobalCursorPosition};
+pub use crate::input::virtual_cursor::VirtualCursor;
 
 pub type ActionRes<'a> = Res<'a, ActionState<Action>>;
 
@@ -13,17 +28,54 @@ pub struct InputPlugin;
 
 impl Plugin for InputPlugin {
     fn build(&self, app: &mut App) {
+        let controls = match Controls::read_from_file() {
+            Ok(controls) => controls,
+            Err(error) => {
+                warn!("Using default controls: {}", error);
+                Controls::default()
+            }
+        };
+
         app.add_plugin(InputManagerPlugin::<Action>::default())
+            .insert_resource(controls)
             .insert_resource(GlobalCursorPosition::default())
+            .insert_resource(VirtualCursor::default())
+            .insert_resource(UiInputClaims::default())
             .add_startup_system(add_input_actions)
-            .add_system(update_global_cursor_position);
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                ui_input_claims_system.before(InputManagerSystem::Update),
+            )
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                input_map_system
+                    .after(ui_input_claims_system)
+                    .before(InputManagerSystem::Update),
+            )
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                virtual_cursor_egui_input_system
+                    .after(InputSystem)
+                    .after(EguiSystem::ProcessInput)
+                    .before(EguiSystem::BeginFrame),
+            )
+            .add_system(virtual_cursor_system)
+            .add_system(update_global_cursor_position.after(virtual_cursor_system))
+            .add_system(virtual_cursor_ui);
     }
 }
 
-#[derive(Actionlike, Eq, PartialEq, Clone, Copy, Hash, Debug)]
+#[derive(
+    Actionlike, Eq, PartialEq, Ord, PartialOrd, Clone, Copy, Hash, Debug, Serialize, Deserialize,
+)]
 pub enum Action {
     HelloAction,
     PrimaryInteraction,
+    /// Moves the [VirtualCursor].
+    CursorMove,
+    Pause,
+    QuickSave,
+    QuickLoad,
     // ---- Camera actions ----
     CameraUp,
     CameraDown,
@@ -31,33 +83,160 @@ pub enum Action {
     CameraRight,
     CameraZoomIn,
     CameraZoomOut,
+    /// Pans the camera at a speed that depends on how far the stick is pushed.
+    CameraPan,
+    /// Zooms continuously while held, for analog triggers.
+    CameraZoomInHold,
+    CameraZoomOutHold,
+    /// Grabs the world under the cursor, to drag the camera around while held.
+    CameraDrag,
+    /// Turns scrolling the camera by moving the cursor to the edge of the window on or off.
+    CameraToggleEdgeScrolling,
+    /// Moves the camera to the selected planets, and zooms to fit them in view.
+    CameraFocusSelected,
+    /// Keeps the ship or planet under the cursor in the center of the screen, until the player
+    /// moves the camera themselves.
+    CameraFollow,
+    /// Stores the current view in a [CameraBookmarks] slot.
+    ///
+    /// [CameraBookmarks]: crate::camera::CameraBookmarks
+    CameraStoreBookmark1,
+    CameraStoreBookmark2,
+    CameraStoreBookmark3,
+    CameraStoreBookmark4,
+    CameraStoreBookmark5,
+    CameraStoreBookmark6,
+    CameraStoreBookmark7,
+    CameraStoreBookmark8,
+    CameraStoreBookmark9,
+    /// Moves the camera to the view stored in a [CameraBookmarks] slot.
+    ///
+    /// [CameraBookmarks]: crate::camera::CameraBookmarks
+    CameraRecallBookmark1,
+    CameraRecallBookmark2,
+    CameraRecallBookmark3,
+    CameraRecallBookmark4,
+    CameraRecallBookmark5,
+    CameraRecallBookmark6,
+    CameraRecallBookmark7,
+    CameraRecallBookmark8,
+    CameraRecallBookmark9,
+}
+
+impl Action {
+    /// The actions that store a camera bookmark, by slot.
+    pub const STORE_BOOKMARK: [Action; BOOKMARK_SLOTS] = [
+        Action::CameraStoreBookmark1,
+        Action::CameraStoreBookmark2,
+        Action::CameraStoreBookmark3,
+        Action::CameraStoreBookmark4,
+        Action::CameraStoreBookmark5,
+        Action::CameraStoreBookmark6,
+        Action::CameraStoreBookmark7,
+        Action::CameraStoreBookmark8,
+        Action::CameraStoreBookmark9,
+    ];
+    /// The actions that recall a camera bookmark, by slot.
+    pub const RECALL_BOOKMARK: [Action; BOOKMARK_SLOTS] = [
+        Action::CameraRecallBookmark1,
+        Action::CameraRecallBookmark2,
+        Action::CameraRecallBookmark3,
+        Action::CameraRecallBookmark4,
+        Action::CameraRecallBookmark5,
+        Action::CameraRecallBookmark6,
+        Action::CameraRecallBookmark7,
+        Action::CameraRecallBookmark8,
+        Action::CameraRecallBookmark9,
+    ];
+
+    /// Whether the action affects the game world (as opposed to the menus), so that it shouldn't
+    /// react to input that is meant for the UI. See [UiInputClaims].
+    pub fn is_world_action(&self) -> bool {
+        !matches!(self, Action::Pause | Action::QuickSave | Action::QuickLoad)
+    }
+
+    /// The camera bookmark slot that the action stores or recalls, counting from 0.
+    pub fn bookmark_slot(&self) -> Option<usize> {
+        Action::STORE_BOOKMARK
+            .iter()
+            .position(|action| action == self)
+            .or_else(|| {
+                Action::RECALL_BOOKMARK
+                    .iter()
+                    .position(|action| action == self)
+            })
+    }
+
+    /// Id of the localization message with the name of this action, as shown in the controls menu.
+    /// The message needs the [Action::message_arguments].
+    pub fn message_id(&self) -> MessageId {
+        match self {
+            Action::HelloAction => MessageId::ActionHello,
+            Action::PrimaryInteraction => MessageId::ActionPrimaryInteraction,
+            Action::CursorMove => MessageId::ActionCursorMove,
+            Action::Pause => MessageId::ActionPause,
+            Action::QuickSave => MessageId::ActionQuickSave,
+            Action::QuickLoad => MessageId::ActionQuickLoad,
+            Action::CameraUp => MessageId::ActionCameraUp,
+            Action::CameraDown => MessageId::ActionCameraDown,
+            Action::CameraLeft => MessageId::ActionCameraLeft,
+            Action::CameraRight => MessageId::ActionCameraRight,
+            Action::CameraZoomIn => MessageId::ActionCameraZoomIn,
+            Action::CameraZoomOut => MessageId::ActionCameraZoomOut,
+            Action::CameraPan => MessageId::ActionCameraPan,
+            Action::CameraZoomInHold => MessageId::ActionCameraZoomInHold,
+            Action::CameraZoomOutHold => MessageId::ActionCameraZoomOutHold,
+            Action::CameraDrag => MessageId::ActionCameraDrag,
+            Action::CameraToggleEdgeScrolling => MessageId::ActionCameraToggleEdgeScrolling,
+            Action::CameraFocusSelected => MessageId::ActionCameraFocusSelected,
+            Action::CameraFollow => MessageId::ActionCameraFollow,
+            Action::CameraStoreBookmark1
+            | Action::CameraStoreBookmark2
+            | Action::CameraStoreBookmark3
+            | Action::CameraStoreBookmark4
+            | Action::CameraStoreBookmark5
+            | Action::CameraStoreBookmark6
+            | Action::CameraStoreBookmark7
+            | Action::CameraStoreBookmark8
+            | Action::CameraStoreBookmark9 => MessageId::ActionCameraStoreBookmark,
+            Action::CameraRecallBookmark1
+            | Action::CameraRecallBookmark2
+            | Action::CameraRecallBookmark3
+            | Action::CameraRecallBookmark4
+            | Action::CameraRecallBookmark5
+            | Action::CameraRecallBookmark6
+            | Action::CameraRecallBookmark7
+            | Action::CameraRecallBookmark8
+            | Action::CameraRecallBookmark9 => MessageId::ActionCameraRecallBookmark,
+        }
+    }
+
+    /// Arguments of the [Action::message_id] message: the bookmark actions get the number
+    /// of their slot as `slot`, counting from 1 like the keys they are bound to.
+    pub fn message_arguments(&self) -> FluentArgs<'static> {
+        let mut arguments = FluentArgs::new();
+        if let Some(slot) = self.bookmark_slot() {
+            arguments.set("slot", slot + 1);
+        }
+        arguments
+    }
 }
 
-fn add_input_actions(mut commands: Commands) {
-    let mut input_map = InputMap::default();
-    input_map
-        .insert(KeyCode::Space, Action::HelloAction)
-        .insert(MouseButton::Left, Action::PrimaryInteraction)
-        .insert(KeyCode::W, Action::CameraUp)
-        .insert(KeyCode::S, Action::CameraDown)
-        .insert(KeyCode::A, Action::CameraLeft)
-        .insert(KeyCode::D, Action::CameraRight)
-        .insert(
-            InputKind::MouseWheel(MouseWheelDirection::Up),
-            Action::CameraZoomIn,
-        )
-        .insert(
-            InputKind::MouseWheel(MouseWheelDirection::Down),
-            Action::CameraZoomOut,
-        )
-        .insert_chord(
-            [
-                InputKind::Modifier(Modifier::Shift),
-                InputKind::Keyboard(KeyCode::H),
-            ],
-            Action::HelloAction,
-        );
-
-    commands.insert_resource(input_map);
+fn add_input_actions(mut commands: Commands, controls: Res<Controls>) {
+    commands.insert_resource(controls.input_map(UiInputClaims::default()));
     commands.insert_resource(ActionState::<Action>::default())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bookmark_actions_know_their_slot() {
+        for slot in 0..BOOKMARK_SLOTS {
+            assert_eq!(Action::STORE_BOOKMARK[slot].bookmark_slot(), Some(slot));
+            assert_eq!(Action::RECALL_BOOKMARK[slot].bookmark_slot(), Some(slot));
+        }
+        assert_eq!(Action::CameraFollow.bookmark_slot(), None);
+    }
+}
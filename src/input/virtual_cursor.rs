@@ -0,0 +1,128 @@
+use crate::input::{Action, ActionRes, Controls};
+use bevy::prelude::*;
+use bevy::window::CursorMoved;
+use bevy_egui::{egui, EguiContext, EguiRenderInputContainer};
+use leafwing_input_manager::user_input::InputKind;
+
+/// Speed of the virtual cursor at full stick deflection, in logical pixels per second.
+const VIRTUAL_CURSOR_SPEED: f32 = 800.0;
+
+const VIRTUAL_CURSOR_RADIUS: f32 = 6.0;
+
+/// Cursor that is moved with a gamepad stick, for players without a mouse.
+/// While it is in use, it replaces the mouse cursor as the source of the [GlobalCursorPosition].
+///
+/// [GlobalCursorPosition]: crate::input::GlobalCursorPosition
+#[derive(Resource, Default)]
+pub struct VirtualCursor {
+    /// In window coordinates, like [Window::cursor_position].
+    /// `None` while the mouse is being used.
+    position: Option<Vec2>,
+}
+
+impl VirtualCursor {
+    pub fn position(&self) -> Option<Vec2> {
+        self.position
+    }
+
+    /// The position in egui coordinates, which start at the top of the window instead of the bottom.
+    pub fn egui_position(&self, window: &Window) -> Option<egui::Pos2> {
+        self.position
+            .map(|position| egui::pos2(position.x, window.height() - position.y))
+    }
+}
+
+pub fn virtual_cursor_system(
+    actions: ActionRes,
+    windows: Res<Windows>,
+    time: Res<Time>,
+    mut cursor_moved_events: EventReader<CursorMoved>,
+    mut virtual_cursor: ResMut<VirtualCursor>,
+) {
+    // Moving the mouse hands control back to it.
+    if cursor_moved_events.iter().count() > 0 {
+        virtual_cursor.position = None;
+    }
+
+    if !actions.pressed(Action::CursorMove) {
+        return;
+    }
+    let Some(stick) = actions.axis_pair(Action::CursorMove) else { return; };
+    let Some(window) = windows.get_primary() else { return; };
+
+    let window_size = Vec2::new(window.width(), window.height());
+    let start = virtual_cursor
+        .position
+        .or_else(|| window.cursor_position())
+        .unwrap_or(window_size / 2.0);
+
+    let position = start + stick.xy() * VIRTUAL_CURSOR_SPEED * time.delta_seconds();
+    virtual_cursor.position = Some(position.clamp(Vec2::ZERO, window_size));
+}
+
+/// Draws the virtual cursor on top of everything else, because there is no system cursor for it.
+pub fn virtual_cursor_ui(
+    mut egui_context: ResMut<EguiContext>,
+    windows: Res<Windows>,
+    virtual_cursor: Res<VirtualCursor>,
+) {
+    let Some(window) = windows.get_primary() else { return; };
+    let Some(center) = virtual_cursor.egui_position(window) else { return; };
+
+    egui_context
+        .ctx_mut()
+        .layer_painter(egui::LayerId::new(
+            egui::Order::Tooltip,
+            egui::Id::new("virtual_cursor"),
+        ))
+        .circle(
+            center,
+            VIRTUAL_CURSOR_RADIUS,
+            egui::Color32::WHITE,
+            egui::Stroke::new(1.0, egui::Color32::BLACK),
+        );
+}
+
+/// Lets the virtual cursor use the UI: egui gets its position as the pointer position, and the
+/// gamepad buttons bound to [Action::PrimaryInteraction] (on their own) as the primary mouse button.
+/// Runs after bevy_egui gathered the input for egui, and before egui gets it.
+pub fn virtual_cursor_egui_input_system(
+    windows: Res<Windows>,
+    virtual_cursor: Res<VirtualCursor>,
+    controls: Res<Controls>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut egui_input: ResMut<EguiRenderInputContainer>,
+) {
+    let Some(window) = windows.get_primary() else { return; };
+    let Some(position) = virtual_cursor.egui_position(window) else { return; };
+    let Some(input) = egui_input.get_mut(&window.id()) else { return; };
+
+    input.events.push(egui::Event::PointerMoved(position));
+
+    let primary_buttons = controls
+        .bindings(Action::PrimaryInteraction)
+        .iter()
+        .filter_map(|binding| match binding.inputs.as_slice() {
+            [InputKind::GamepadButton(button)] => Some(*button),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let button_changes = gamepad_buttons
+        .get_just_pressed()
+        .map(|button| (button, true))
+        .chain(
+            gamepad_buttons
+                .get_just_released()
+                .map(|button| (button, false)),
+        );
+    for (button, pressed) in button_changes {
+        if primary_buttons.contains(&button.button_type) {
+            input.events.push(egui::Event::PointerButton {
+                pos: position,
+                button: egui::PointerButton::Primary,
+                pressed,
+                modifiers: input.modifiers,
+            });
+        }
+    }
+}
@@ -2,12 +2,28 @@
 #![warn(clippy::all)]
 
 mod camera;
+mod economy;
+mod galaxy;
 mod input;
-mod localization;
+pub mod localization;
+mod menu;
+mod ron_file;
+mod save;
+mod settings;
+mod simulation;
+mod transport;
 
 use crate::camera::GameCameraPlugin;
+use crate::economy::{Demand, EconomyPlugin, Good, Inventory, Production};
+use crate::galaxy::{GalaxyAssets, GalaxyPlugin};
 use crate::input::{Action, ActionRes, GlobalCursorPosition, InputPlugin};
-use crate::localization::{Localization, LocalizationAssets, LocalizationPlugin};
+use crate::localization::{
+    FluentArgs, Localization, LocalizationAssets, LocalizationPlugin, MessageId,
+};
+use crate::menu::MenuPlugin;
+use crate::save::SavePlugin;
+use crate::settings::SettingsPlugin;
+use crate::transport::{LineDrawing, Ship, TransportLine, TransportPlugin};
 use bevy::log::LogPlugin;
 use bevy::math::Vec3Swizzles;
 use bevy::prelude::*;
@@ -15,15 +31,8 @@ use bevy_asset_loader::prelude::*;
 use bevy_egui::{egui, EguiContext, EguiPlugin};
 use tracing::info;
 
-const GREETING_ID: &str = "greeting";
-
 const UI_MARGIN: f32 = 10.0;
 
-// TODO (Wybe 2022-12-30): Mouse clicks on the UI should somehow not affect the game itself.
-//      probably using `Res<UiClaimsMouse>`. But how can we do this with Leafwing input manager?
-//      because the whole point of the input manager is to decouple the fact that the input came in
-//      via the mouse.
-
 pub struct MainPlugin;
 
 impl Plugin for MainPlugin {
@@ -48,25 +57,34 @@ impl Plugin for MainPlugin {
             filter: "info,wgpu_core=warn,wgpu_hal=warn".into(),
         }));
 
-        app.add_plugin(InputPlugin)
+        app.add_plugin(SettingsPlugin)
+            .add_plugin(InputPlugin)
             .add_plugin(LocalizationPlugin)
             .add_plugin(GameCameraPlugin)
+            .add_plugin(TransportPlugin)
+            .add_plugin(EconomyPlugin)
+            .add_plugin(GalaxyPlugin)
+            .add_plugin(SavePlugin)
+            .add_plugin(MenuPlugin)
             .add_plugin(EguiPlugin)
             .add_loading_state(
                 LoadingState::new(GameState::AssetLoading)
-                    .continue_to_state(GameState::Main)
+                    .continue_to_state(GameState::MainMenu)
                     .with_dynamic_collections::<StandardDynamicAssetCollection>(vec![
                         "dynamic_assets.assets",
                     ])
                     .with_collection::<ImageAssets>()
+                    .with_collection::<GalaxyAssets>()
                     .with_collection::<LocalizationAssets>(),
             )
             .add_state(GameState::AssetLoading)
-            .add_system_set(SystemSet::on_enter(GameState::Main).with_system(spawn_planets))
+            .add_system_set(SystemSet::on_exit(GameState::Main).with_system(despawn_game_world))
             .add_system_set(
                 SystemSet::on_update(GameState::Main)
                     .with_system(greeting_system)
                     .with_system(planet_selection_system)
+                    .with_system(planet_sprite_system)
+                    .with_system(planet_scale_system)
                     .with_system(planet_info_ui)
                     .with_system(main_actions_ui),
             );
@@ -82,28 +100,71 @@ struct ImageAssets {
 #[derive(Clone, Eq, PartialEq, Debug, Hash)]
 enum GameState {
     AssetLoading,
+    MainMenu,
     Main,
+    /// Pushed on top of [GameState::Main]. Stops the simulation, but the camera can still move.
+    Paused,
+    /// Pushed on top of either [GameState::MainMenu] or [GameState::Paused].
+    Settings,
+    /// Pushed on top of [GameState::Settings].
+    Controls,
 }
 
 #[derive(Component)]
 struct Planet {
     name: String,
+    /// Radius in game units. Determines both how big the planet is drawn, and where it can be clicked.
+    radius: f32,
 }
 
 #[derive(Component)]
 struct Selected;
 
-fn spawn_planets(mut commands: Commands, image_assets: Res<ImageAssets>) {
-    for index in 0..10 {
+/// Cleans up after a game, when going back to the main menu.
+fn despawn_game_world(
+    mut commands: Commands,
+    game_entities: Query<Entity, Or<(With<Planet>, With<TransportLine>, With<Ship>)>>,
+) {
+    for entity in game_entities.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    commands.remove_resource::<LineDrawing>();
+}
+
+/// The planet image is drawn at this radius when it isn't scaled.
+const PLANET_SPRITE_RADIUS: f32 = 16.0;
+
+fn spawn_planet(
+    commands: &mut Commands,
+    planet: Planet,
+    position: Vec2,
+    production: Production,
+    demand: Demand,
+) -> Entity {
+    commands
+        .spawn(SpatialBundle::from_transform(Transform::from_translation(
+            position.extend(0.0),
+        )))
+        .insert(planet)
+        .insert(Inventory::default())
+        .insert(production)
+        .insert(demand)
+        .id()
+}
+
+/// Gives newly spawned planets their sprite.
+/// This is separate from [spawn_planet], so that planets can be spawned without the image assets.
+fn planet_sprite_system(
+    mut commands: Commands,
+    image_assets: Res<ImageAssets>,
+    new_planets: Query<Entity, Added<Planet>>,
+) {
+    for planet in new_planets.iter() {
         commands
-            .spawn(SpriteBundle {
-                texture: image_assets.planet.clone(),
-                transform: Transform::from_xyz(index as f32 * 100.0, 0.0, 0.0),
-                ..default()
-            })
-            .insert(Planet {
-                name: format!("Planet {index}"),
-            });
+            .entity(planet)
+            .insert(Sprite::default())
+            .insert(image_assets.planet.clone());
     }
 }
 
@@ -111,73 +172,146 @@ fn planet_selection_system(
     mut commands: Commands,
     actions: ActionRes,
     cursor_position: Res<GlobalCursorPosition>,
-    planets: Query<(Entity, &GlobalTransform, Option<&Selected>), With<Planet>>,
+    planets: Query<(Entity, &GlobalTransform, &Planet, Option<&Selected>)>,
+    line_drawing: Option<Res<LineDrawing>>,
 ) {
-    if !actions.just_pressed(Action::PrimaryInteraction) {
+    // While drawing a transport line, clicking on planets adds stops instead of selecting them.
+    if line_drawing.is_some() || !actions.just_pressed(Action::PrimaryInteraction) {
         return;
     }
 
-    let mut found_planet = false;
-
-    for (entity, transform, maybe_selected) in planets.iter() {
-        // TODO (Wybe 2022-12-30): Put in planet sizes.
-        if cursor_position.distance(transform.translation().xy()) < 16.0 {
-            found_planet = true;
+    let clicked_planet = planet_under_cursor(
+        **cursor_position,
+        planets
+            .iter()
+            .map(|(entity, transform, planet, _)| (entity, transform, planet)),
+    );
 
+    match clicked_planet.and_then(|entity| planets.get(entity).ok()) {
+        Some((entity, _, _, maybe_selected)) => {
             if maybe_selected.is_none() {
                 commands.entity(entity).insert(Selected);
             } else {
                 commands.entity(entity).remove::<Selected>();
             }
         }
-    }
-
-    if !found_planet {
-        // No planets found? Deselect all.
-        for (entity, _, maybe_selected) in planets.iter() {
-            if maybe_selected.is_some() {
-                commands.entity(entity).remove::<Selected>();
+        None => {
+            // No planets found? Deselect all.
+            for (entity, _, _, maybe_selected) in planets.iter() {
+                if maybe_selected.is_some() {
+                    commands.entity(entity).remove::<Selected>();
+                }
             }
         }
     }
 }
 
+/// Returns the planet closest to the cursor, out of the planets the cursor is on.
+fn planet_under_cursor<'a>(
+    cursor_position: Vec2,
+    planets: impl IntoIterator<Item = (Entity, &'a GlobalTransform, &'a Planet)>,
+) -> Option<Entity> {
+    planets
+        .into_iter()
+        .map(|(entity, transform, planet)| {
+            let distance = cursor_position.distance(transform.translation().xy());
+            (entity, distance, planet.radius)
+        })
+        .filter(|(_, distance, radius)| distance < radius)
+        .min_by(|(_, a, _), (_, b, _)| a.total_cmp(b))
+        .map(|(entity, _, _)| entity)
+}
+
+/// Scales the planet sprites to match the radius of the planets.
+fn planet_scale_system(mut planets: Query<(&Planet, &mut Transform), Changed<Planet>>) {
+    for (planet, mut transform) in planets.iter_mut() {
+        transform.scale = Vec3::splat(planet.radius / PLANET_SPRITE_RADIUS);
+    }
+}
+
 fn planet_info_ui(
     mut egui_context: ResMut<EguiContext>,
-    selected_planets: Query<&Planet, With<Selected>>,
+    localization: Res<Localization>,
+    selected_planets: Query<(Entity, &Planet, &Inventory), With<Selected>>,
+    lines: Query<&TransportLine>,
+    ships: Query<&Ship>,
 ) {
     if selected_planets.is_empty() {
         return;
     }
 
+    let (anchor, offset) =
+        localization.mirror_anchor(egui::Align2::LEFT_TOP, (UI_MARGIN, UI_MARGIN));
     egui::Window::new("selected_planets")
         .title_bar(false)
         .resizable(false)
-        .anchor(egui::Align2::LEFT_TOP, (UI_MARGIN, UI_MARGIN))
+        .anchor(anchor, offset)
         .show(egui_context.ctx_mut(), |ui| {
-            for planet in selected_planets.iter() {
-                ui.label(&planet.name);
-            }
+            ui.with_layout(localization.vertical_layout(), |ui| {
+                for (entity, planet, inventory) in selected_planets.iter() {
+                    ui.label(&planet.name);
+
+                    for good in Good::ALL {
+                        let mut arguments = FluentArgs::new();
+                        arguments.set("good", localization.localize(good.message_id()));
+                        arguments.set("amount", inventory.amount(good).floor());
+                        ui.label(
+                            localization.localize_with_args(MessageId::PlanetStock, &arguments),
+                        );
+                    }
+
+                    let visiting_ships = ships
+                        .iter()
+                        .filter(|ship| {
+                            lines
+                                .get(ship.line)
+                                .map_or(false, |line| line.stops.contains(&entity))
+                        })
+                        .count();
+                    let mut arguments = FluentArgs::new();
+                    arguments.set("count", visiting_ships);
+                    ui.label(
+                        localization.localize_with_args(MessageId::PlanetVisitingShips, &arguments),
+                    );
+                }
+            });
         });
 }
 
-fn main_actions_ui(mut egui_context: ResMut<EguiContext>, localization: Res<Localization>) {
+fn main_actions_ui(
+    mut commands: Commands,
+    mut egui_context: ResMut<EguiContext>,
+    localization: Res<Localization>,
+    line_drawing: Option<Res<LineDrawing>>,
+    lines: Query<(), With<TransportLine>>,
+    ships: Query<(), With<Ship>>,
+) {
+    let (anchor, offset) =
+        localization.mirror_anchor(egui::Align2::LEFT_BOTTOM, (UI_MARGIN, -UI_MARGIN));
     egui::Window::new("main_actions")
         .title_bar(false)
         .resizable(false)
-        .anchor(egui::Align2::LEFT_BOTTOM, (UI_MARGIN, -UI_MARGIN))
+        .anchor(anchor, offset)
         .show(egui_context.ctx_mut(), |ui| {
-            if ui
-                .button(localization.localize("new_transport_line"))
-                .clicked()
-            {
-                println!("New line button clicked");
-            }
+            ui.with_layout(localization.vertical_layout(), |ui| {
+                let mut arguments = FluentArgs::new();
+                arguments.set("lines", lines.iter().count());
+                arguments.set("ships", ships.iter().count());
+                ui.label(localization.localize_with_args(MessageId::TransportSummary, &arguments));
+
+                let new_line_button = ui.add_enabled(
+                    line_drawing.is_none(),
+                    egui::Button::new(localization.localize(MessageId::NewTransportLine)),
+                );
+                if new_line_button.clicked() {
+                    commands.insert_resource(LineDrawing::default());
+                }
+            });
         });
 }
 
 fn greeting_system(actions: ActionRes, localization: Res<Localization>) {
     if actions.just_pressed(Action::HelloAction) {
-        info!("{}", localization.localize(GREETING_ID));
+        info!("{}", localization.localize(MessageId::Greeting));
     }
 }
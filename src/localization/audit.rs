@@ -0,0 +1,290 @@
+use crate::localization::languages::LANGUAGE_FALLBACKS_ID;
+use crate::localization::FluentLanguage;
+use fluent_syntax::ast;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use unic_langid::LanguageIdentifier;
+
+/// The message ids of a language, with the names of the variables used by each message.
+type MessageSignatures<'a> = BTreeMap<&'a str, BTreeSet<&'a str>>;
+
+/// The differences between a language and the reference language (English).
+/// See [audit_language].
+#[derive(Debug)]
+pub struct LanguageAudit {
+    pub language_id: LanguageIdentifier,
+    /// Messages that the reference language has, but this language doesn't.
+    pub missing: Vec<String>,
+    /// Messages that only this language has. Usually left over after a message was renamed or removed.
+    pub extra: Vec<String>,
+    pub argument_mismatches: Vec<ArgumentMismatch>,
+}
+
+/// A message that uses different variables than the same message in the reference language.
+#[derive(Debug)]
+pub struct ArgumentMismatch {
+    pub message_id: String,
+    /// Variables that the reference message uses, but this translation doesn't.
+    pub missing: Vec<String>,
+    /// Variables that this translation uses, but which the game never passes to the message.
+    pub unknown: Vec<String>,
+}
+
+impl LanguageAudit {
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty() && self.argument_mismatches.is_empty()
+    }
+}
+
+impl fmt::Display for LanguageAudit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_complete() {
+            return writeln!(f, "`{}` is complete", self.language_id);
+        }
+
+        writeln!(f, "`{}` is incomplete", self.language_id)?;
+        for message_id in &self.missing {
+            writeln!(f, "  missing: {}", message_id)?;
+        }
+        for message_id in &self.extra {
+            writeln!(f, "  extra: {}", message_id)?;
+        }
+        for mismatch in &self.argument_mismatches {
+            write!(f, "  arguments of {}:", mismatch.message_id)?;
+            if !mismatch.missing.is_empty() {
+                write!(f, " missing ${}", mismatch.missing.join(", $"))?;
+            }
+            if !mismatch.unknown.is_empty() {
+                write!(f, " unknown ${}", mismatch.unknown.join(", $"))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares the messages of `language` with those of `reference`, and lists the messages
+/// that are missing, extra, or that use different variables.
+pub fn audit_language(reference: &FluentLanguage, language: &FluentLanguage) -> LanguageAudit {
+    let reference_messages = message_signatures(reference);
+    let messages = message_signatures(language);
+
+    let missing = reference_messages
+        .keys()
+        .filter(|id| !messages.contains_key(*id))
+        .map(|id| id.to_string())
+        .collect();
+
+    // The fallbacks are specific to each language, so English not having them is fine.
+    let extra = messages
+        .keys()
+        .filter(|id| !reference_messages.contains_key(*id) && **id != LANGUAGE_FALLBACKS_ID)
+        .map(|id| id.to_string())
+        .collect();
+
+    let argument_mismatches = reference_messages
+        .iter()
+        .filter_map(|(id, reference_variables)| {
+            let variables = messages.get(id)?;
+            let mismatch = ArgumentMismatch {
+                message_id: id.to_string(),
+                missing: reference_variables
+                    .difference(variables)
+                    .map(|variable| variable.to_string())
+                    .collect(),
+                unknown: variables
+                    .difference(reference_variables)
+                    .map(|variable| variable.to_string())
+                    .collect(),
+            };
+            (!mismatch.missing.is_empty() || !mismatch.unknown.is_empty()).then_some(mismatch)
+        })
+        .collect();
+
+    LanguageAudit {
+        language_id: language.identifier().clone(),
+        missing,
+        extra,
+        argument_mismatches,
+    }
+}
+
+fn message_signatures(language: &FluentLanguage) -> MessageSignatures<'_> {
+    language
+        .resource()
+        .entries()
+        .filter_map(|entry| match entry {
+            ast::Entry::Message(message) => Some(message),
+            _ => None,
+        })
+        .map(|message| {
+            let mut variables = BTreeSet::new();
+            let patterns = message
+                .value
+                .iter()
+                .chain(message.attributes.iter().map(|attribute| &attribute.value));
+            for pattern in patterns {
+                collect_pattern_variables(pattern, &mut variables);
+            }
+            (message.id.name, variables)
+        })
+        .collect()
+}
+
+fn collect_pattern_variables<'a>(
+    pattern: &ast::Pattern<&'a str>,
+    variables: &mut BTreeSet<&'a str>,
+) {
+    for element in &pattern.elements {
+        if let ast::PatternElement::Placeable { expression } = element {
+            collect_expression_variables(expression, variables);
+        }
+    }
+}
+
+fn collect_expression_variables<'a>(
+    expression: &ast::Expression<&'a str>,
+    variables: &mut BTreeSet<&'a str>,
+) {
+    match expression {
+        ast::Expression::Select { selector, variants } => {
+            collect_inline_variables(selector, variables);
+            for variant in variants {
+                collect_pattern_variables(&variant.value, variables);
+            }
+        }
+        ast::Expression::Inline(expression) => collect_inline_variables(expression, variables),
+    }
+}
+
+fn collect_inline_variables<'a>(
+    expression: &ast::InlineExpression<&'a str>,
+    variables: &mut BTreeSet<&'a str>,
+) {
+    match expression {
+        ast::InlineExpression::VariableReference { id } => {
+            variables.insert(id.name);
+        }
+        ast::InlineExpression::FunctionReference { arguments, .. }
+        | ast::InlineExpression::TermReference {
+            arguments: Some(arguments),
+            ..
+        } => {
+            let expressions = arguments
+                .positional
+                .iter()
+                .chain(arguments.named.iter().map(|argument| &argument.value));
+            for expression in expressions {
+                collect_inline_variables(expression, variables);
+            }
+        }
+        ast::InlineExpression::Placeable { expression } => {
+            collect_expression_variables(expression, variables)
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::localization::testing::language;
+
+    const REFERENCE: &str = "
+greeting = Hello
+ship_count = { $count ->
+    [one] { $count } ship
+   *[other] { $count } ships
+}
+planet_stock = { $good ->
+    [food] Food
+   *[other] { $good }
+}: { $amount }
+";
+
+    #[test]
+    fn complete_translation_passes() {
+        let reference = language("en-US", REFERENCE);
+        let translation = language(
+            "nl-NL",
+            "
+language_fallbacks = de-DE
+greeting = Hallo
+ship_count = { $count ->
+    [one] één schip
+   *[other] { $count } schepen
+}
+planet_stock = { $good }: { $amount }
+",
+        );
+
+        let audit = audit_language(&reference, &translation);
+
+        assert!(audit.is_complete(), "{}", audit);
+    }
+
+    #[test]
+    fn finds_missing_and_extra_messages() {
+        let reference = language("en-US", REFERENCE);
+        let translation = language(
+            "nl-NL",
+            "
+ship_count = { $count } schepen
+planet_stock = { $good }: { $amount }
+planet_name = Planeet
+",
+        );
+
+        let audit = audit_language(&reference, &translation);
+
+        assert_eq!(audit.missing, ["greeting"]);
+        assert_eq!(audit.extra, ["planet_name"]);
+        assert!(audit.argument_mismatches.is_empty());
+    }
+
+    #[test]
+    fn finds_argument_mismatches_in_select_expressions() {
+        let reference = language("en-US", REFERENCE);
+        let translation = language(
+            "nl-NL",
+            "
+greeting = Hallo
+ship_count = { $amount ->
+    [one] één schip
+   *[other] { $amount } schepen
+}
+planet_stock = { $good ->
+    [food] Voedsel
+   *[other] { $goods }
+}: { $amount }
+",
+        );
+
+        let audit = audit_language(&reference, &translation);
+
+        assert!(audit.missing.is_empty());
+        assert!(audit.extra.is_empty());
+        let mismatches = audit
+            .argument_mismatches
+            .iter()
+            .map(|mismatch| {
+                (
+                    mismatch.message_id.as_str(),
+                    mismatch.missing.clone(),
+                    mismatch.unknown.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            mismatches,
+            [
+                ("planet_stock", vec![], vec!["goods".to_string()]),
+                (
+                    "ship_count",
+                    vec!["count".to_string()],
+                    vec!["amount".to_string()]
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,142 @@
+use fluent::types::FluentType;
+use fluent::FluentValue;
+use intl_memoizer::Memoizable;
+use std::borrow::Cow;
+use std::convert::Infallible;
+use unic_langid::LanguageIdentifier;
+
+/// A calendar date, to use as an argument of a localized message:
+/// `arguments.set("date", FluentDate::new(2026, 10, 18))`.
+/// Written with numbers, in the order and with the separator that the language uses,
+/// so that no month names have to be translated.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FluentDate {
+    pub year: i32,
+    /// From 1 to 12.
+    pub month: u32,
+    pub day: u32,
+}
+
+impl FluentDate {
+    pub fn new(year: i32, month: u32, day: u32) -> Self {
+        FluentDate { year, month, day }
+    }
+}
+
+impl From<FluentDate> for FluentValue<'static> {
+    fn from(date: FluentDate) -> Self {
+        FluentValue::Custom(Box::new(date))
+    }
+}
+
+impl FluentType for FluentDate {
+    fn duplicate(&self) -> Box<dyn FluentType + Send> {
+        Box::new(*self)
+    }
+
+    fn as_string(&self, intls: &intl_memoizer::IntlLangMemoizer) -> Cow<'static, str> {
+        intls
+            .with_try_get::<DateFormat, _, _>((), |format| format.format(self))
+            .unwrap_or_else(|error| match error {})
+            .into()
+    }
+
+    fn as_string_threadsafe(
+        &self,
+        intls: &intl_memoizer::concurrent::IntlLangMemoizer,
+    ) -> Cow<'static, str> {
+        intls
+            .with_try_get::<DateFormat, _, _>((), |format| format.format(self))
+            .unwrap_or_else(|error| match error {})
+            .into()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum DateOrder {
+    DayMonthYear,
+    MonthDayYear,
+    YearMonthDay,
+}
+
+/// How a language writes dates with numbers.
+/// Worked out once per language, and then kept by the language's [IntlLangMemoizer].
+///
+/// [IntlLangMemoizer]: intl_memoizer::concurrent::IntlLangMemoizer
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct DateFormat {
+    order: DateOrder,
+    separator: &'static str,
+    /// Whether the day and month are always written with two digits, like `05`.
+    padded: bool,
+}
+
+impl DateFormat {
+    fn format(&self, date: &FluentDate) -> String {
+        let (day, month) = if self.padded {
+            (format!("{:02}", date.day), format!("{:02}", date.month))
+        } else {
+            (date.day.to_string(), date.month.to_string())
+        };
+        let year = date.year.to_string();
+
+        let parts = match self.order {
+            DateOrder::DayMonthYear => [day, month, year],
+            DateOrder::MonthDayYear => [month, day, year],
+            DateOrder::YearMonthDay => [year, month, day],
+        };
+        parts.join(self.separator)
+    }
+}
+
+impl Memoizable for DateFormat {
+    type Args = ();
+    type Error = Infallible;
+
+    fn construct(language: LanguageIdentifier, _: ()) -> Result<Self, Self::Error> {
+        let region = language.region.as_ref().map(|region| region.as_str());
+        let format = |order, separator, padded| DateFormat {
+            order,
+            separator,
+            padded,
+        };
+
+        // Only the most common conventions. Other languages get day, month, year with slashes,
+        // which is understood in most of the world.
+        Ok(match (language.language.as_str(), region) {
+            ("en", None | Some("US")) => format(DateOrder::MonthDayYear, "/", false),
+            ("zh" | "ja", _) => format(DateOrder::YearMonthDay, "/", false),
+            ("sv" | "lt", _) => format(DateOrder::YearMonthDay, "-", true),
+            ("nl" | "fy", _) => format(DateOrder::DayMonthYear, "-", false),
+            ("de" | "da" | "fi" | "nb" | "cs" | "pl" | "ru" | "tr" | "uk", _) => {
+                format(DateOrder::DayMonthYear, ".", true)
+            }
+            _ => format(DateOrder::DayMonthYear, "/", true),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::localization::testing::language;
+    use fluent::FluentArgs;
+
+    fn localize_date(language_id: &str, date: FluentDate) -> String {
+        let language = language(language_id, "saved = Saved on { $date }");
+        let mut arguments = FluentArgs::new();
+        arguments.set("date", date);
+        language.localize_with_args("saved", &arguments).unwrap()
+    }
+
+    #[test]
+    fn dates_follow_the_language() {
+        let date = FluentDate::new(2026, 3, 5);
+
+        assert_eq!(localize_date("en-US", date), "Saved on 3/5/2026");
+        assert_eq!(localize_date("en-GB", date), "Saved on 05/03/2026");
+        assert_eq!(localize_date("nl-NL", date), "Saved on 5-3-2026");
+        assert_eq!(localize_date("de-DE", date), "Saved on 05.03.2026");
+        assert_eq!(localize_date("sv-SE", date), "Saved on 2026-03-05");
+    }
+}
@@ -1,17 +1,25 @@
 use bevy::asset::{AssetLoader, BoxedFuture, LoadContext, LoadedAsset};
 use bevy::prelude::*;
 use bevy::reflect::TypeUuid;
-use fluent::{bundle::FluentBundle, FluentArgs, FluentResource};
+use fluent::bundle::FluentBundle;
+use fluent::{FluentArgs, FluentError, FluentResource, FluentValue};
+use fluent_syntax::parser::ErrorKind;
 use intl_memoizer::concurrent::IntlLangMemoizer;
-use std::sync::Arc;
+use std::fmt;
+use std::sync::{Arc, Mutex};
 use thiserror::Error;
 use tracing::error;
-use unic_langid::{LanguageIdentifier, LanguageIdentifierError};
+use unic_langid::{CharacterDirection, LanguageIdentifier, LanguageIdentifierError};
 
 /// Message that the language loader expects to be available in `.ftl` files,
 /// so that the language can be identified with a human-readable name.
 const LANGUAGE_NAME_ID: &str = "language_name";
 
+/// Optional message in `.ftl` files, listing the identifiers of the languages to try (in order)
+/// when a message is missing from this language. For example: `language_fallbacks = nl-NL`.
+/// Separated by commas and/or whitespace.
+pub(crate) const LANGUAGE_FALLBACKS_ID: &str = "language_fallbacks";
+
 /// `*.ftl` localization file representing a single language for use with [fluent](https://github.com/projectfluent/fluent-rs).
 /// The name of the file should be a valid language identifier, for example `en-US.ftl`.
 #[derive(TypeUuid)]
@@ -19,6 +27,13 @@ const LANGUAGE_NAME_ID: &str = "language_name";
 pub struct FluentLanguage {
     language_identifier: LanguageIdentifier,
     name: String,
+    fallbacks: Vec<LanguageIdentifier>,
+    /// The parsed `.ftl` file, kept so that its messages can be inspected.
+    /// See [crate::localization::audit_language].
+    resource: Arc<FluentResource>,
+    /// Problems found while loading the `.ftl` file. The parts of the file without problems
+    /// are still usable, so a single typo doesn't make the whole language unavailable.
+    load_errors: Vec<FluentFileError>,
     /// The [IntlLangMemoizer] type needs to be specified because we need the concurrent version
     /// of it. The default [FluentBundle] uses the non-Sync version.
     bundle: FluentBundle<Arc<FluentResource>, IntlLangMemoizer>,
@@ -27,20 +42,43 @@ pub struct FluentLanguage {
 impl FluentLanguage {
     pub fn new(
         language_identifier: LanguageIdentifier,
+        resource: Arc<FluentResource>,
         bundle: FluentBundle<Arc<FluentResource>, IntlLangMemoizer>,
+        load_errors: Vec<FluentFileError>,
     ) -> FluentLanguage {
         let mut language = FluentLanguage {
             language_identifier,
             name: LANGUAGE_NAME_ID.to_string(),
+            fallbacks: vec![],
+            resource,
+            load_errors,
             bundle,
         };
 
         language.name = language
             .localize(LANGUAGE_NAME_ID)
             .unwrap_or_else(|_| LANGUAGE_NAME_ID.to_string());
+
+        if let Ok(fallbacks) = language.localize(LANGUAGE_FALLBACKS_ID) {
+            language.fallbacks = fallbacks
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|id| !id.is_empty())
+                .filter_map(|id| match id.parse::<LanguageIdentifier>() {
+                    Ok(id) => Some(id),
+                    Err(error) => {
+                        warn!(
+                            "Ignoring fallback `{}` of language `{}`: {}",
+                            id, language.language_identifier, error
+                        );
+                        None
+                    }
+                })
+                .collect();
+        }
+
         debug!(
-            "Loaded language with id `{}` and name `{}`",
-            language.language_identifier, language.name
+            "Loaded language with id `{}` and name `{}`, with fallbacks {:?}",
+            language.language_identifier, language.name, language.fallbacks
         );
 
         language
@@ -50,20 +88,49 @@ impl FluentLanguage {
         &self.language_identifier
     }
 
+    /// Human-readable name of the language, in the language itself.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the language is written from right to left, like Arabic or Hebrew.
+    pub fn is_right_to_left(&self) -> bool {
+        self.language_identifier.character_direction() == CharacterDirection::RTL
+    }
+
+    /// Languages to try, in order, when a message is missing from this language.
+    pub fn fallbacks(&self) -> &[LanguageIdentifier] {
+        &self.fallbacks
+    }
+
+    /// The parsed contents of the `.ftl` file this language was loaded from.
+    pub fn resource(&self) -> &FluentResource {
+        &self.resource
+    }
+
+    /// Problems in the `.ftl` file. Messages affected by them are missing from the language.
+    pub fn load_errors(&self) -> &[FluentFileError] {
+        &self.load_errors
+    }
+
+    /// All [FluentLanguage::load_errors], each on their own line(s).
+    pub fn load_errors_listing(&self) -> String {
+        list_errors(&self.load_errors)
+    }
+
     pub fn localize(&self, message_id: &str) -> Result<String, LocalizationError> {
-        self.localize_with_args(message_id, &[])
+        self.localize_with_args(message_id, &FluentArgs::new())
     }
 
+    /// The arguments can be strings or numbers. Numbers are formatted according to the language,
+    /// and can be used to select plural variants (`[one]`, `*[other]`) in the message.
+    /// Dates can be passed as a [crate::localization::FluentDate], and are written the way
+    /// the language writes them.
     pub fn localize_with_args(
         &self,
         message_id: &str,
-        arguments: &[(&str, &str)],
+        arguments: &FluentArgs,
     ) -> Result<String, LocalizationError> {
-        let mut fluent_args = FluentArgs::new();
-        for (key, value) in arguments {
-            fluent_args.set(<&str>::clone(key), <&str>::clone(value));
-        }
-
         let message =
             self.bundle
                 .get_message(message_id)
@@ -79,27 +146,53 @@ impl FluentLanguage {
         let mut errors = vec![];
         let result = self
             .bundle
-            .format_pattern(pattern, Some(&fluent_args), &mut errors);
+            .format_pattern(pattern, Some(arguments), &mut errors);
 
         if !errors.is_empty() {
-            let errors_string = errors
-                .iter()
-                .enumerate()
-                // TODO (Wybe 2022-06-06): Clean up this error reporting?
-                .map(|(i, err)| format!("\n{}: {:?}", i, err))
-                .collect::<String>();
-
-            warn!(
-                "Errors while localizing `{}` for language `{}`, with arguments {:x?}:{}",
-                message_id, self.language_identifier, arguments, errors_string
-            );
+            let error = LocalizationError::FormattingFailed {
+                message_id: message_id.to_string(),
+                language_id: self.language_identifier.clone(),
+                errors,
+            };
+            warn!("{}\nArguments: {}", error, list_arguments(arguments));
         }
         Ok(result.to_string())
     }
 }
 
+/// Loads `.ftl` files as [FluentLanguage]s.
+///
+/// A file that isn't named after a language, like a backup called `fy-NL.old.ftl`, is skipped
+/// instead of failing: a failed asset would keep the language folder from ever finishing loading.
+/// Why it was skipped ends up in `skipped_files`, to be shown to the player.
 #[derive(Default)]
-pub struct FluentLanguageLoader;
+pub struct FluentLanguageLoader {
+    pub(crate) skipped_files: SkippedLanguageFiles,
+}
+
+/// Descriptions of the `.ftl` files that [FluentLanguageLoader] skipped.
+/// Shared with the loader, which runs on another thread.
+#[derive(Resource, Clone, Default)]
+pub struct SkippedLanguageFiles(Arc<Mutex<Vec<String>>>);
+
+impl SkippedLanguageFiles {
+    fn push(&self, description: String) {
+        self.0
+            .lock()
+            .expect("The language loader doesn't panic while holding the lock")
+            .push(description);
+    }
+
+    /// Removes and returns the descriptions gathered so far.
+    pub fn take(&self) -> Vec<String> {
+        std::mem::take(
+            &mut *self
+                .0
+                .lock()
+                .expect("The language loader doesn't panic while holding the lock"),
+        )
+    }
+}
 
 impl AssetLoader for FluentLanguageLoader {
     fn load<'a>(
@@ -117,7 +210,26 @@ impl AssetLoader for FluentLanguageLoader {
 
             let fluent_string = String::from_utf8_lossy(bytes).to_string();
 
-            let language = load_language_from_fluent_string(fluent_string, language_id)?;
+            let language = match load_language_from_fluent_string(fluent_string, language_id) {
+                Ok(language) => language,
+                Err(error) => {
+                    let description = format!(
+                        "Skipped `{}`, the file name should be a language identifier like `en-US.ftl`: {}",
+                        load_context.path().display(),
+                        error
+                    );
+                    warn!("{}", description);
+                    self.skipped_files.push(description);
+                    return Ok(());
+                }
+            };
+            if !language.load_errors().is_empty() {
+                warn!(
+                    "Problems in `{}`, the affected messages are left out:{}",
+                    load_context.path().display(),
+                    language.load_errors_listing()
+                );
+            }
 
             load_context.set_default_asset(LoadedAsset::new(language));
             Ok(())
@@ -129,34 +241,42 @@ impl AssetLoader for FluentLanguageLoader {
     }
 }
 
-fn load_language_from_fluent_string(
+/// Parses the contents of an `.ftl` file into a language.
+/// `lang_id_string` is the identifier of the language, like `en-US`, normally taken from the file name.
+/// Problems in the file itself don't fail the loading, they end up in [FluentLanguage::load_errors].
+pub fn load_language_from_fluent_string(
     fluent_string: String,
     lang_id_string: &str,
 ) -> Result<FluentLanguage, LanguageLoadingError> {
-    let resource = FluentResource::try_new(fluent_string).map_err(|(_, errors)| {
-        let error_listing = errors
-            .iter()
-            .enumerate()
-            .map(|(i, err)| format!("\n{}: {:?}", i, err))
-            .collect::<String>();
-
-        LanguageLoadingError::ParsingFluentFileFailed { error_listing }
-    })?;
-
     let id = lang_id_string.parse::<LanguageIdentifier>()?;
 
-    let mut bundle = FluentBundle::new_concurrent(vec![id.clone()]);
-    bundle.add_resource(Arc::new(resource)).map_err(|errors| {
-        let error_listing = errors
-            .iter()
-            .enumerate()
-            .map(|(i, err)| format!("\n{}: {:?}", i, err))
-            .collect::<String>();
+    let (resource, mut load_errors) = match FluentResource::try_new(fluent_string) {
+        Ok(resource) => (resource, vec![]),
+        Err((resource, errors)) => {
+            let load_errors = errors
+                .into_iter()
+                .map(|error| FluentFileError::new(resource.source(), FluentError::from(error)))
+                .collect();
+            (resource, load_errors)
+        }
+    };
+    let resource = Arc::new(resource);
 
-        LanguageLoadingError::ParsingFluentFileFailed { error_listing }
-    })?;
+    let mut bundle = FluentBundle::new_concurrent(vec![id.clone()]);
+    // Fluent surrounds arguments with Unicode bidi isolation marks, so that for example a
+    // left-to-right planet name can't mess up a right-to-left sentence. Egui doesn't handle
+    // bidirectional text, and would show the marks as unknown characters, so leave them out.
+    bundle.set_use_isolating(false);
+    // Entries that clash with earlier ones are skipped, the rest is still added.
+    if let Err(errors) = bundle.add_resource(resource.clone()) {
+        load_errors.extend(
+            errors
+                .into_iter()
+                .map(|error| FluentFileError::new(resource.source(), error)),
+        );
+    }
 
-    Ok(FluentLanguage::new(id, bundle))
+    Ok(FluentLanguage::new(id, resource, bundle, load_errors))
 }
 
 #[derive(Error, Debug)]
@@ -166,21 +286,252 @@ pub enum LocalizationError {
         message_id: String,
         language_id: LanguageIdentifier,
     },
+
+    #[error("No language with id `{language_id}` is loaded")]
+    LanguageNotLoaded { language_id: LanguageIdentifier },
+
+    /// The message was found, but parts of it could not be filled in, like a variable that
+    /// wasn't given. Fluent still produces a result in that case, with the broken parts marked.
+    #[error("Errors while localizing `{message_id}` for language `{language_id}`:{}", list_errors(.errors))]
+    FormattingFailed {
+        message_id: String,
+        language_id: LanguageIdentifier,
+        errors: Vec<FluentError>,
+    },
 }
 
 #[derive(Error, Debug)]
-enum LanguageLoadingError {
+pub enum LanguageLoadingError {
     #[error("Got a language asset without file name. The file name should be a valid language identifier, like `en-US.ftl`")]
     AssetWithoutFileName,
 
     #[error(transparent)]
     ParsingLanguageIdentifierFailed(#[from] LanguageIdentifierError),
+}
+
+/// A problem with an `.ftl` file, and where in the file it is.
+#[derive(Debug)]
+pub struct FluentFileError {
+    /// `None` if the problem can't be traced back to a single place in the file.
+    pub location: Option<SourceLocation>,
+    pub kind: FluentFileErrorKind,
+}
+
+#[derive(Error, Debug)]
+pub enum FluentFileErrorKind {
+    #[error("{0}")]
+    Syntax(ErrorKind),
 
-    #[error("Could not parse `.ftl` file:\n{error_listing}")]
-    ParsingFluentFileFailed {
-        /// The `FluentResource::try_new` and `FluentBundle.add_resource` functions return
-        /// a vector of FluentErrors.
-        /// Their messages should be collected in-order, and put in this string.
-        error_listing: String,
+    #[error("The {kind} `{id}` is defined more than once")]
+    Duplicate {
+        /// `message` or `term`.
+        kind: String,
+        id: String,
     },
+
+    #[error(transparent)]
+    Other(FluentError),
+}
+
+/// A position in a text file.
+#[derive(Debug)]
+pub struct SourceLocation {
+    /// Starts at 1, like in text editors.
+    pub line: usize,
+    /// Starts at 1, like in text editors. Counted in characters, not bytes.
+    pub column: usize,
+    /// The full line the position is on, to show the problem in context.
+    pub line_text: String,
+}
+
+impl FluentFileError {
+    /// `source` is the contents of the `.ftl` file that caused the error.
+    fn new(source: &str, error: FluentError) -> FluentFileError {
+        match error {
+            FluentError::ParserError(error) => FluentFileError {
+                location: Some(SourceLocation::new(source, error.pos.start)),
+                kind: FluentFileErrorKind::Syntax(error.kind),
+            },
+            // Fluent doesn't say where the duplicate is, so look for the last definition.
+            FluentError::Overriding { kind, id } => {
+                let kind = kind.to_string();
+                // Terms are written with a leading `-`, which Fluent leaves out of their id.
+                let written_id = if kind == "term" {
+                    format!("-{}", id)
+                } else {
+                    id.clone()
+                };
+                FluentFileError {
+                    location: find_definition(source, &written_id)
+                        .map(|position| SourceLocation::new(source, position)),
+                    kind: FluentFileErrorKind::Duplicate { kind, id },
+                }
+            }
+            error => FluentFileError {
+                location: None,
+                kind: FluentFileErrorKind::Other(error),
+            },
+        }
+    }
+}
+
+impl fmt::Display for FluentFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(location) = &self.location else { return write!(f, "{}", self.kind); };
+
+        writeln!(
+            f,
+            "line {}, column {}: {}",
+            location.line, location.column, self.kind
+        )?;
+        writeln!(f, "    {}", location.line_text)?;
+        write!(f, "    {}^", " ".repeat(location.column - 1))
+    }
+}
+
+impl SourceLocation {
+    /// `position` is a byte offset into `source`. An offset inside a multi-byte character
+    /// counts as the start of that character.
+    fn new(source: &str, position: usize) -> SourceLocation {
+        let mut position = position.min(source.len());
+        while !source.is_char_boundary(position) {
+            position -= 1;
+        }
+        let before = &source[..position];
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+
+        SourceLocation {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            line_text: source[line_start..]
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+        }
+    }
+}
+
+/// Byte offset of the start of the last line that defines the message or term with the given id.
+/// The id is as written in the file, so with the leading `-` for terms.
+fn find_definition(source: &str, written_id: &str) -> Option<usize> {
+    let mut definition = None;
+    let mut line_start = 0;
+    for line in source.split_inclusive('\n') {
+        let defines_id = line
+            .strip_prefix(written_id)
+            .map_or(false, |rest| rest.trim_start().starts_with('='));
+        if defines_id {
+            definition = Some(line_start);
+        }
+        line_start += line.len();
+    }
+    definition
+}
+
+/// Lists the arguments as `name = value`, to show in log messages.
+fn list_arguments(arguments: &FluentArgs) -> String {
+    let arguments = arguments
+        .iter()
+        .map(|(name, value)| match value {
+            FluentValue::String(string) => format!("{} = \"{}\"", name, string),
+            FluentValue::Number(number) => format!("{} = {}", name, number.as_string()),
+            FluentValue::Custom(custom) => format!("{} = {:?}", name, custom),
+            FluentValue::None | FluentValue::Error => format!("{} = (none)", name),
+        })
+        .collect::<Vec<_>>();
+    if arguments.is_empty() {
+        "(none)".to_string()
+    } else {
+        arguments.join(", ")
+    }
+}
+
+/// Puts every error on its own line(s), numbered in order.
+fn list_errors(errors: &[impl fmt::Display]) -> String {
+    errors
+        .iter()
+        .enumerate()
+        .map(|(i, error)| format!("\n{}: {}", i, error))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn location_column_counts_characters() {
+        let source = "first = één\nsecond = ünïcödé\n";
+        let location = SourceLocation::new(source, source.find("cödé").unwrap());
+
+        assert_eq!(location.line, 2);
+        assert_eq!(location.column, "second = ünï".chars().count() + 1);
+        assert_eq!(location.line_text, "second = ünïcödé");
+    }
+
+    #[test]
+    fn location_inside_character_points_at_that_character() {
+        let source = "name = ü!";
+        let location = SourceLocation::new(source, source.find('ü').unwrap() + 1);
+
+        assert_eq!((location.line, location.column), (1, 8));
+    }
+
+    #[test]
+    fn location_past_the_end_points_at_the_end() {
+        let source = "a = b\nc = d";
+        let location = SourceLocation::new(source, source.len() + 10);
+
+        assert_eq!((location.line, location.column), (2, 6));
+        assert_eq!(location.line_text, "c = d");
+    }
+
+    #[test]
+    fn definition_of_message_is_not_confused_with_term() {
+        let source = "brand = Message\n-brand = Term\nbrandy = Drink\n";
+
+        assert_eq!(find_definition(source, "brand"), Some(0));
+        assert_eq!(find_definition(source, "-brand"), source.find("-brand"));
+        assert_eq!(find_definition(source, "bran"), None);
+    }
+
+    #[test]
+    fn definition_is_the_last_one() {
+        let source = "greeting = Hi\ngreeting = Hello\n";
+
+        assert_eq!(
+            find_definition(source, "greeting"),
+            source.find("greeting = Hello")
+        );
+    }
+
+    #[test]
+    fn term_with_name_of_message_is_reported_at_the_term() {
+        let source = "language_name = Test\nbrand = Message\n-brand = Term\n";
+        let language = load_language_from_fluent_string(source.to_string(), "en-US").unwrap();
+
+        let [error] = language.load_errors() else {
+            panic!("expected one error, got {:?}", language.load_errors());
+        };
+        assert!(matches!(
+            &error.kind,
+            FluentFileErrorKind::Duplicate { kind, id } if kind == "term" && id == "brand"
+        ));
+        assert_eq!(
+            error.location.as_ref().map(|location| location.line),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn arguments_are_listed_by_name() {
+        let mut arguments = FluentArgs::new();
+        arguments.set("planet", "Kepler");
+        arguments.set("count", 3);
+
+        // Fluent keeps the arguments sorted by name.
+        assert_eq!(list_arguments(&arguments), "count = 3, planet = \"Kepler\"");
+        assert_eq!(list_arguments(&FluentArgs::new()), "(none)");
+    }
 }
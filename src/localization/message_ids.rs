@@ -0,0 +1,11 @@
+use std::fmt;
+
+// Defines the `MessageId` enum, with a variant for every message in the English `.ftl` file.
+// See `build.rs`.
+include!(concat!(env!("OUT_DIR"), "/message_ids.rs"));
+
+impl fmt::Display for MessageId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
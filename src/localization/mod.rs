@@ -1,30 +1,56 @@
+mod audit;
+mod date;
 mod languages;
+mod message_ids;
+mod system_locale;
 
-use crate::localization::languages::{FluentLanguage, FluentLanguageLoader};
-use crate::GameState;
+use crate::localization::languages::{FluentLanguageLoader, SkippedLanguageFiles};
+use crate::localization::system_locale::system_locales;
+use crate::settings::Settings;
+use crate::{GameState, UI_MARGIN};
 use bevy::prelude::*;
 use bevy_asset_loader::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use fluent_langneg::{negotiate_languages, NegotiationStrategy};
 use itertools::Itertools;
+use unic_langid::LanguageIdentifier;
+
+pub use crate::localization::audit::{audit_language, ArgumentMismatch, LanguageAudit};
+pub use crate::localization::date::FluentDate;
+pub use crate::localization::languages::{
+    load_language_from_fluent_string, FluentFileError, FluentFileErrorKind, FluentLanguage,
+    LanguageLoadingError, LocalizationError, SourceLocation,
+};
+pub use crate::localization::message_ids::MessageId;
+pub use fluent::FluentArgs;
 
 const DEFAULT_LANGUAGE_IDENTIFIER: &str = "en-US";
 
+/// English, built into the executable. Used when `en-US.ftl` is missing or has problems,
+/// so that there is always some text to show.
+const BUILT_IN_LANGUAGE: &str = include_str!("../../assets/localization/en-US.ftl");
+
 pub struct LocalizationPlugin;
 
 impl Plugin for LocalizationPlugin {
     fn build(&self, app: &mut App) {
+        let language_loader = FluentLanguageLoader::default();
         app.add_asset::<FluentLanguage>()
-            .init_asset_loader::<FluentLanguageLoader>()
+            .insert_resource(language_loader.skipped_files.clone())
+            .add_asset_loader(language_loader)
             .add_system_set(
                 SystemSet::on_exit(GameState::AssetLoading).with_system(initialize_localization),
-            );
+            )
+            .add_system(localization_hot_reload_system)
+            .add_system(localization_warnings_ui);
     }
 }
 
 /// This resource only exists during the [GameState::AssetLoading] state, and is
 /// used there so that the asset loader plugin can load the language files.
 ///
-/// Afterwards, the language structs will be moved to the [Localization] resource by
-/// [initialize_localization]
+/// Afterwards, the language structs (and their handles) will be moved to the [Localization]
+/// resource by [initialize_localization]
 #[derive(AssetCollection, Resource)]
 pub struct LocalizationAssets {
     #[asset(key = "language_files", collection(typed))]
@@ -33,28 +59,224 @@ pub struct LocalizationAssets {
 
 #[derive(Resource)]
 pub struct Localization {
-    // TODO (Wybe 2022-12-18): Add a configurable fallback language
-    //   (because falling back on english doesn't always make sense. For example: Frysian would probably want Dutch as fallback).
-    //   And _only_ after that fallback language fails, will we try english.
     current_language_index: usize,
+    /// Indices into `languages`, in the order in which they are tried when localizing a message.
+    /// See [Localization::update_fallback_chain].
+    fallback_chain: Vec<usize>,
     languages: Vec<FluentLanguage>,
+    /// Tried when none of the languages in the `fallback_chain` have a message.
+    built_in_language: FluentLanguage,
+    /// The asset handles the `languages` were loaded from.
+    /// Kept around so that changes to the `.ftl` files can be picked up while the game is running.
+    /// See [localization_hot_reload_system].
+    handles: Vec<Handle<FluentLanguage>>,
 }
 
 impl Localization {
-    pub fn localize(&self, message_id: &str) -> String {
-        // TODO (Wybe 2022-12-18): Error handling, or fallback, or maybe if the language is not found we want to auto-select english?
-        let language = &self.languages[self.current_language_index];
-
-        match language.localize(message_id) {
-            Ok(result) => result,
-            Err(error) => {
-                warn!("{}", error);
-                message_id.to_string()
+    fn new(
+        languages: Vec<FluentLanguage>,
+        handles: Vec<Handle<FluentLanguage>>,
+        current_language_index: usize,
+    ) -> Self {
+        let mut localization = Localization {
+            current_language_index,
+            fallback_chain: vec![],
+            languages,
+            built_in_language: load_built_in_language(),
+            handles,
+        };
+        localization.update_fallback_chain();
+        localization
+    }
+
+    /// Localizes the message in the current language.
+    /// If the current language doesn't have the message, its fallback languages are tried,
+    /// then the built-in English. If none of those have it, the message id itself is returned.
+    pub fn localize(&self, message_id: MessageId) -> String {
+        self.localize_with_args(message_id, &FluentArgs::new())
+    }
+
+    /// Like [Localization::localize], but for messages with variables in them.
+    /// For example, `ship_count` can be given a number `count`, to get either "1 ship" or "3 ships".
+    /// See [FluentLanguage::localize_with_args] for the kinds of arguments that are supported.
+    pub fn localize_with_args(&self, message_id: MessageId, arguments: &FluentArgs) -> String {
+        let localized = self
+            .fallback_chain
+            .iter()
+            .filter_map(|index| self.languages.get(*index))
+            .chain([&self.built_in_language])
+            .find_map(|language| {
+                language
+                    .localize_with_args(message_id.as_str(), arguments)
+                    .ok()
+            });
+
+        localized.unwrap_or_else(|| {
+            warn!(
+                "Couldn't find message with id `{}` for language `{}`, or any of its fallbacks",
+                message_id,
+                self.current_language().identifier()
+            );
+            message_id.to_string()
+        })
+    }
+
+    /// All the languages that can be selected.
+    pub fn languages(&self) -> impl Iterator<Item = &FluentLanguage> {
+        self.languages.iter()
+    }
+
+    pub fn current_language(&self) -> &FluentLanguage {
+        &self.languages[self.current_language_index]
+    }
+
+    /// Whether the current language is written from right to left, like Arabic or Hebrew.
+    /// The UI is mirrored for those languages.
+    pub fn is_right_to_left(&self) -> bool {
+        self.current_language().is_right_to_left()
+    }
+
+    /// Layout for widgets below each other, aligned to the side where lines of text start
+    /// in the current language.
+    pub fn vertical_layout(&self) -> egui::Layout {
+        if self.is_right_to_left() {
+            egui::Layout::top_down(egui::Align::Max)
+        } else {
+            egui::Layout::top_down(egui::Align::Min)
+        }
+    }
+
+    /// Layout for widgets next to each other, in the reading order of the current language.
+    pub fn horizontal_layout(&self) -> egui::Layout {
+        if self.is_right_to_left() {
+            egui::Layout::right_to_left(egui::Align::Center)
+        } else {
+            egui::Layout::left_to_right(egui::Align::Center)
+        }
+    }
+
+    /// Mirrors a window anchor (and its offset) horizontally for right-to-left languages,
+    /// so that a window in the top left corner of the screen ends up in the top right corner.
+    pub fn mirror_anchor(
+        &self,
+        align: egui::Align2,
+        offset: impl Into<egui::Vec2>,
+    ) -> (egui::Align2, egui::Vec2) {
+        let offset = offset.into();
+        if !self.is_right_to_left() {
+            return (align, offset);
+        }
+
+        let horizontal = match align.x() {
+            egui::Align::Min => egui::Align::Max,
+            egui::Align::Center => egui::Align::Center,
+            egui::Align::Max => egui::Align::Min,
+        };
+        (
+            egui::Align2([horizontal, align.y()]),
+            egui::vec2(-offset.x, offset.y),
+        )
+    }
+
+    /// Switches the language used by [Localization::localize].
+    pub fn select_language(
+        &mut self,
+        language_id: &LanguageIdentifier,
+    ) -> Result<(), LocalizationError> {
+        let (index, _) = self
+            .languages
+            .iter()
+            .find_position(|language| language.identifier() == language_id)
+            .ok_or_else(|| LocalizationError::LanguageNotLoaded {
+                language_id: language_id.clone(),
+            })?;
+
+        self.current_language_index = index;
+        self.update_fallback_chain();
+        Ok(())
+    }
+
+    /// Selects the loaded language that best matches the `requested` languages, which are in order
+    /// of preference. Regional variants count as a match, so `nl-BE` can select `nl-NL`.
+    /// Returns the identifier of the selected language, if any of them matched.
+    pub fn select_best_language(
+        &mut self,
+        requested: &[LanguageIdentifier],
+    ) -> Option<LanguageIdentifier> {
+        let available = self
+            .languages
+            .iter()
+            .map(|language| language.identifier().clone())
+            .collect::<Vec<_>>();
+        let best = negotiate_languages(requested, &available, None, NegotiationStrategy::Lookup)
+            .first()
+            .map(|id| (*id).clone())?;
+
+        self.select_language(&best).ok()?;
+        Some(best)
+    }
+
+    fn language_index(&self, language_id: &LanguageIdentifier) -> Option<usize> {
+        self.languages
+            .iter()
+            .position(|language| language.identifier() == language_id)
+    }
+
+    /// Puts a (re)loaded language in place of the language with the same identifier,
+    /// or adds it if there is no such language yet.
+    fn replace_language(&mut self, language: FluentLanguage) {
+        match self.language_index(language.identifier()) {
+            Some(index) => self.languages[index] = language,
+            None => self.languages.push(language),
+        }
+        self.update_fallback_chain();
+    }
+
+    /// Works out the order in which languages are tried when localizing a message:
+    /// first the current language, then its fallbacks (and their fallbacks, and so on),
+    /// and only then English.
+    /// Every language is tried at most once, so fallbacks that refer to each other are no problem.
+    fn update_fallback_chain(&mut self) {
+        let mut chain = vec![self.current_language_index];
+
+        let mut next = 0;
+        while let Some(language) = chain.get(next).and_then(|index| self.languages.get(*index)) {
+            for fallback_id in language.fallbacks() {
+                match self.language_index(fallback_id) {
+                    Some(index) if !chain.contains(&index) => chain.push(index),
+                    Some(_) => {}
+                    None => warn!(
+                        "Fallback language `{}` of language `{}` is not loaded",
+                        fallback_id,
+                        language.identifier()
+                    ),
+                }
             }
+            next += 1;
         }
+
+        if let Some(default_index) = self
+            .languages
+            .iter()
+            .position(|language| *language.identifier() == DEFAULT_LANGUAGE_IDENTIFIER)
+        {
+            if !chain.contains(&default_index) {
+                chain.push(default_index);
+            }
+        }
+
+        self.fallback_chain = chain;
     }
 }
 
+/// Problems with the language files that the player should know about, because they make
+/// (parts of) the game show up in another language than expected.
+/// Shown in a window until dismissed, see [localization_warnings_ui].
+#[derive(Resource, Default)]
+pub struct LocalizationWarnings {
+    warnings: Vec<String>,
+}
+
 /// System that should be called after all the languages in the `LocalizationAssets` resource has been loaded.
 /// Removes all [FluentLanguage]s listed in [LocalizationAssets] and creates a [Localization] resource for
 /// convenience.
@@ -62,23 +284,203 @@ fn initialize_localization(
     mut commands: Commands,
     localization_assets: Res<LocalizationAssets>,
     mut language_assets: ResMut<Assets<FluentLanguage>>,
+    settings: Res<Settings>,
+    skipped_language_files: Res<SkippedLanguageFiles>,
 ) {
-    let languages: Vec<FluentLanguage> = localization_assets
-        .languages
-        .iter()
-        .filter_map(|handle| language_assets.remove(handle))
-        .collect();
+    let mut warnings = skipped_language_files.take();
+    let mut handles = vec![];
+    let mut languages = vec![];
+    for handle in localization_assets.languages.iter() {
+        let Some(language) = language_assets.remove(handle) else { continue; };
 
-    let default_language_index = languages
+        if !language.load_errors().is_empty() {
+            warnings.push(format!(
+                "`{}.ftl` has problems, the affected messages are left out:{}",
+                language.identifier(),
+                language.load_errors_listing()
+            ));
+        }
+        handles.push(handle.clone());
+        languages.push(language);
+    }
+
+    let default_language_index = match languages
         .iter()
-        .find_position(|language| *language.identifier() == DEFAULT_LANGUAGE_IDENTIFIER)
-        .map(|(index, _)| index)
-        .expect("Cannot start, need at least a language file for English (en-US.ftl).");
+        .position(|language| *language.identifier() == DEFAULT_LANGUAGE_IDENTIFIER)
+    {
+        Some(index) => index,
+        None => {
+            warnings.push(format!(
+                "`{}.ftl` could not be loaded, using the built-in English instead.",
+                DEFAULT_LANGUAGE_IDENTIFIER
+            ));
+            languages.push(load_built_in_language());
+            languages.len() - 1
+        }
+    };
+
+    let mut localization = Localization::new(languages, handles, default_language_index);
+
+    // Restore the language the player picked last time,
+    // or pick the one matching the operating system if they never did.
+    if let Some(language_id) = &settings.language {
+        let selected = language_id
+            .parse::<LanguageIdentifier>()
+            .map_err(|error| error.to_string())
+            .and_then(|id| {
+                localization
+                    .select_language(&id)
+                    .map_err(|error| error.to_string())
+            });
+        if let Err(error) = selected {
+            warnings.push(format!(
+                "Could not restore language `{}`: {}",
+                language_id, error
+            ));
+        }
+    } else {
+        let system_locales = system_locales();
+        match localization.select_best_language(&system_locales) {
+            Some(language_id) => info!(
+                "Selected language `{}` to match the system locale {:?}",
+                language_id, system_locales
+            ),
+            None => info!(
+                "No language matches the system locale {:?}, using `{}`",
+                system_locales,
+                localization.current_language().identifier()
+            ),
+        }
+    }
 
-    commands.insert_resource(Localization {
-        current_language_index: default_language_index,
-        languages,
-    });
+    for warning in warnings.iter() {
+        warn!("{}", warning);
+    }
+
+    commands.insert_resource(localization);
+    commands.insert_resource(LocalizationWarnings { warnings });
 
     commands.remove_resource::<LocalizationAssets>();
 }
+
+fn load_built_in_language() -> FluentLanguage {
+    load_language_from_fluent_string(BUILT_IN_LANGUAGE.to_string(), DEFAULT_LANGUAGE_IDENTIFIER)
+        .expect("The identifier of the built-in language is valid")
+}
+
+/// When the asset server is watching for changes (in debug builds), edited `.ftl` files are
+/// loaded again. This takes those new versions out of the assets, and swaps them into the
+/// [Localization] resource, so that translators can see their changes without restarting.
+fn localization_hot_reload_system(
+    mut asset_events: EventReader<AssetEvent<FluentLanguage>>,
+    mut language_assets: ResMut<Assets<FluentLanguage>>,
+    localization: Option<ResMut<Localization>>,
+) {
+    let Some(mut localization) = localization else { return; };
+
+    for event in asset_events.iter() {
+        let (AssetEvent::Created { handle } | AssetEvent::Modified { handle }) = event else { continue; };
+        if !localization.handles.contains(handle) {
+            continue;
+        }
+        let Some(language) = language_assets.remove(handle) else { continue; };
+
+        info!("Reloaded language `{}`", language.identifier());
+        localization.replace_language(language);
+    }
+}
+
+fn localization_warnings_ui(
+    mut egui_context: ResMut<EguiContext>,
+    localization: Option<Res<Localization>>,
+    localization_warnings: Option<ResMut<LocalizationWarnings>>,
+) {
+    let Some(localization) = localization else { return; };
+    let Some(mut localization_warnings) = localization_warnings else { return; };
+    if localization_warnings.warnings.is_empty() {
+        return;
+    }
+
+    egui::Window::new(localization.localize(MessageId::LocalizationWarnings))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_TOP, (0.0, UI_MARGIN))
+        .show(egui_context.ctx_mut(), |ui| {
+            for warning in localization_warnings.warnings.iter() {
+                ui.label(warning);
+            }
+
+            if ui
+                .button(localization.localize(MessageId::LocalizationWarningsDismiss))
+                .clicked()
+            {
+                localization_warnings.warnings.clear();
+            }
+        });
+}
+
+#[cfg(test)]
+pub mod testing {
+    use super::*;
+
+    /// A language with the given identifier, loaded from the given `.ftl` contents.
+    pub fn language(id: &str, fluent_string: &str) -> FluentLanguage {
+        load_language_from_fluent_string(fluent_string.to_string(), id).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::localization::testing::language;
+
+    fn chain_identifiers(localization: &Localization) -> Vec<String> {
+        localization
+            .fallback_chain
+            .iter()
+            .map(|index| localization.languages[*index].identifier().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn cyclic_fallbacks_end_in_english() {
+        let languages = vec![
+            language("en-US", "greeting = Hello\nnew_transport_line = New line"),
+            language(
+                "nl-NL",
+                "language_fallbacks = fy-NL, de-DE\ngreeting = Hallo",
+            ),
+            language("fy-NL", "language_fallbacks = nl-NL"),
+            language("de-DE", "language_fallbacks = fy-NL, nl-NL"),
+        ];
+        let mut localization = Localization::new(languages, vec![], 0);
+
+        localization
+            .select_language(&"fy-NL".parse().unwrap())
+            .unwrap();
+
+        assert_eq!(
+            chain_identifiers(&localization),
+            ["fy-NL", "nl-NL", "de-DE", "en-US"]
+        );
+        assert_eq!(localization.localize(MessageId::Greeting), "Hallo");
+        assert_eq!(
+            localization.localize(MessageId::NewTransportLine),
+            "New line"
+        );
+    }
+
+    #[test]
+    fn regional_variant_selects_same_language() {
+        let languages = vec![
+            language("en-US", "greeting = Hello"),
+            language("nl-NL", "greeting = Hallo"),
+        ];
+        let mut localization = Localization::new(languages, vec![], 0);
+
+        let selected = localization.select_best_language(&["nl-BE".parse().unwrap()]);
+
+        assert_eq!(selected, Some("nl-NL".parse().unwrap()));
+        assert_eq!(*localization.current_language().identifier(), "nl-NL");
+    }
+}
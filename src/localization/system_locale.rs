@@ -0,0 +1,48 @@
+use std::env;
+use unic_langid::LanguageIdentifier;
+
+/// Environment variables that can hold the locale, in order of precedence.
+const LOCALE_VARIABLES: [&str; 3] = ["LC_ALL", "LC_MESSAGES", "LANG"];
+
+/// The languages the operating system is set to, most preferred first.
+/// Read from the POSIX locale environment variables, so on Windows this usually finds nothing.
+pub fn system_locales() -> Vec<LanguageIdentifier> {
+    LOCALE_VARIABLES
+        .iter()
+        .filter_map(|variable| env::var(variable).ok())
+        .filter_map(|locale| parse_posix_locale(&locale))
+        .collect()
+}
+
+/// Turns a POSIX locale like `nl_BE.UTF-8@euro` into a language identifier like `nl-BE`.
+/// The `C` and `POSIX` locales don't say anything about the language, so they give `None`.
+fn parse_posix_locale(locale: &str) -> Option<LanguageIdentifier> {
+    let language = locale.split(|c| c == '.' || c == '@').next()?;
+    if language.is_empty() || language == "C" || language == "POSIX" {
+        return None;
+    }
+
+    language.replace('_', "-").parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encoding_and_modifier_are_ignored() {
+        assert_eq!(
+            parse_posix_locale("nl_BE.UTF-8@euro"),
+            Some("nl-BE".parse().unwrap())
+        );
+        assert_eq!(parse_posix_locale("fy_NL"), Some("fy-NL".parse().unwrap()));
+    }
+
+    #[test]
+    fn c_and_posix_locales_have_no_language() {
+        assert_eq!(parse_posix_locale("C"), None);
+        assert_eq!(parse_posix_locale("C.UTF-8"), None);
+        assert_eq!(parse_posix_locale("POSIX"), None);
+        assert_eq!(parse_posix_locale(""), None);
+    }
+}
@@ -0,0 +1,353 @@
+use crate::input::{capture_binding, Action, ActionRes, Controls};
+use crate::localization::{FluentArgs, Localization, MessageId};
+use crate::save::{PendingLoad, SaveGame, SaveGameRequest, QUICKSAVE_PATH};
+use crate::settings::Settings;
+use crate::GameState;
+use bevy::app::AppExit;
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use leafwing_input_manager::Actionlike;
+
+const MENU_BUTTON_WIDTH: f32 = 200.0;
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(pause_system)
+            .add_system_set(SystemSet::on_update(GameState::MainMenu).with_system(main_menu_ui))
+            .add_system_set(SystemSet::on_update(GameState::Paused).with_system(pause_menu_ui))
+            .add_system_set(SystemSet::on_update(GameState::Settings).with_system(settings_ui))
+            .add_system_set(SystemSet::on_update(GameState::Controls).with_system(controls_ui));
+    }
+}
+
+/// Pauses and unpauses the game.
+fn pause_system(actions: ActionRes, mut state: ResMut<State<GameState>>) {
+    if !actions.just_pressed(Action::Pause) {
+        return;
+    }
+
+    let result = match state.current().clone() {
+        GameState::Main => state.push(GameState::Paused),
+        GameState::Paused => state.pop(),
+        _ => Ok(()),
+    };
+
+    if let Err(error) = result {
+        warn!("Could not (un)pause the game: {:?}", error);
+    }
+}
+
+/// Shows a centered window with the given contents, in the style of the menus.
+fn menu_window(egui_context: &mut EguiContext, id: &str, add_contents: impl FnOnce(&mut egui::Ui)) {
+    egui::Window::new(id)
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, (0.0, 0.0))
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.set_width(MENU_BUTTON_WIDTH);
+            ui.vertical_centered_justified(add_contents);
+        });
+}
+
+fn main_menu_ui(
+    mut commands: Commands,
+    mut egui_context: ResMut<EguiContext>,
+    localization: Res<Localization>,
+    mut state: ResMut<State<GameState>>,
+    mut exit_events: EventWriter<AppExit>,
+) {
+    menu_window(&mut egui_context, "main_menu", |ui| {
+        if ui
+            .button(localization.localize(MessageId::MenuNewGame))
+            .clicked()
+        {
+            state.set(GameState::Main).ok();
+        }
+
+        if ui
+            .button(localization.localize(MessageId::MenuLoadGame))
+            .clicked()
+        {
+            match SaveGame::read_from_file(QUICKSAVE_PATH) {
+                Ok(save) => {
+                    commands.insert_resource(PendingLoad(save));
+                    state.set(GameState::Main).ok();
+                }
+                Err(error) => error!("Could not load game from `{}`: {}", QUICKSAVE_PATH, error),
+            }
+        }
+
+        if ui
+            .button(localization.localize(MessageId::MenuSettings))
+            .clicked()
+        {
+            state.push(GameState::Settings).ok();
+        }
+
+        if ui
+            .button(localization.localize(MessageId::MenuQuit))
+            .clicked()
+        {
+            exit_events.send(AppExit);
+        }
+    });
+}
+
+fn pause_menu_ui(
+    mut egui_context: ResMut<EguiContext>,
+    localization: Res<Localization>,
+    mut state: ResMut<State<GameState>>,
+    mut save_requests: EventWriter<SaveGameRequest>,
+    mut exit_events: EventWriter<AppExit>,
+) {
+    menu_window(&mut egui_context, "pause_menu", |ui| {
+        ui.heading(localization.localize(MessageId::MenuPaused));
+
+        if ui
+            .button(localization.localize(MessageId::MenuResume))
+            .clicked()
+        {
+            state.pop().ok();
+        }
+
+        if ui
+            .button(localization.localize(MessageId::MenuSaveGame))
+            .clicked()
+        {
+            save_requests.send(SaveGameRequest);
+        }
+
+        if ui
+            .button(localization.localize(MessageId::MenuSettings))
+            .clicked()
+        {
+            state.push(GameState::Settings).ok();
+        }
+
+        if ui
+            .button(localization.localize(MessageId::MenuMainMenu))
+            .clicked()
+        {
+            state.replace(GameState::MainMenu).ok();
+        }
+
+        if ui
+            .button(localization.localize(MessageId::MenuQuit))
+            .clicked()
+        {
+            exit_events.send(AppExit);
+        }
+    });
+}
+
+fn settings_ui(
+    mut egui_context: ResMut<EguiContext>,
+    mut localization: ResMut<Localization>,
+    mut settings: ResMut<Settings>,
+    mut state: ResMut<State<GameState>>,
+) {
+    menu_window(&mut egui_context, "settings", |ui| {
+        ui.heading(localization.localize(MessageId::MenuSettings));
+
+        let mut selected_language = None;
+        egui::ComboBox::from_label(localization.localize(MessageId::SettingsLanguage))
+            .selected_text(localization.current_language().name())
+            .show_ui(ui, |ui| {
+                for language in localization.languages() {
+                    let is_current =
+                        language.identifier() == localization.current_language().identifier();
+                    if ui.selectable_label(is_current, language.name()).clicked() {
+                        selected_language = Some(language.identifier().clone());
+                    }
+                }
+            });
+
+        if let Some(language_id) = selected_language {
+            if let Err(error) = localization.select_language(&language_id) {
+                error!("{}", error);
+            } else {
+                settings.language = Some(language_id.to_string());
+                settings.save();
+            }
+        }
+
+        if ui
+            .button(localization.localize(MessageId::MenuControls))
+            .clicked()
+        {
+            state.push(GameState::Controls).ok();
+        }
+
+        if ui
+            .button(localization.localize(MessageId::MenuBack))
+            .clicked()
+        {
+            state.pop().ok();
+        }
+    });
+}
+
+/// The binding that the player is changing in the controls menu.
+struct Rebinding {
+    action: Action,
+    /// `None` when adding a new binding.
+    index: Option<usize>,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn controls_ui(
+    mut egui_context: ResMut<EguiContext>,
+    localization: Res<Localization>,
+    mut controls: ResMut<Controls>,
+    mut state: ResMut<State<GameState>>,
+    mut rebinding: Local<Option<Rebinding>>,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut mouse_wheel_events: EventReader<MouseWheel>,
+) {
+    let mut changed = false;
+
+    if let Some(current) = rebinding.as_ref() {
+        // Clicks on the menu itself are meant for the menu, not to be bound to an action.
+        let capture_mouse = !egui_context.ctx_mut().is_pointer_over_area();
+        if let Some(binding) = capture_binding(
+            &keys,
+            &mouse_buttons,
+            &gamepad_buttons,
+            &mut mouse_wheel_events,
+            capture_mouse,
+        ) {
+            controls.set_binding(current.action, current.index, binding);
+            *rebinding = None;
+            changed = true;
+        }
+    }
+
+    egui::Window::new("controls")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, (0.0, 0.0))
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.vertical_centered(|ui| ui.heading(localization.localize(MessageId::MenuControls)));
+
+            egui::Grid::new("bindings").striped(true).show(ui, |ui| {
+                for action in Action::variants() {
+                    ui.label(localization.localize_with_args(
+                        action.message_id(),
+                        &action.message_arguments(),
+                    ));
+
+                    let mut conflicting_actions = vec![];
+                    let mut removed = None;
+                    ui.horizontal(|ui| {
+                        for (index, binding) in controls.bindings(action).iter().enumerate() {
+                            let is_rebinding = matches!(
+                                rebinding.as_ref(),
+                                Some(current) if current.action == action && current.index == Some(index)
+                            );
+                            let text = if is_rebinding {
+                                localization.localize(MessageId::ControlsPressInput)
+                            } else {
+                                binding.to_string()
+                            };
+
+                            let conflicts = controls.conflicts(action, binding);
+                            let button = if conflicts.is_empty() {
+                                egui::Button::new(text)
+                            } else {
+                                egui::Button::new(
+                                    egui::RichText::new(text).color(ui.visuals().warn_fg_color),
+                                )
+                            };
+                            let response = ui
+                                .add(button)
+                                .on_hover_text(localization.localize(MessageId::ControlsBindingHint));
+
+                            if response.clicked() {
+                                *rebinding = Some(Rebinding {
+                                    action,
+                                    index: Some(index),
+                                });
+                            }
+                            if response.secondary_clicked() {
+                                removed = Some(index);
+                            }
+                            conflicting_actions.extend(conflicts);
+                        }
+
+                        let is_adding = matches!(
+                            rebinding.as_ref(),
+                            Some(current) if current.action == action && current.index.is_none()
+                        );
+                        if is_adding {
+                            ui.label(localization.localize(MessageId::ControlsPressInput));
+                        } else if ui.small_button("+").clicked() {
+                            *rebinding = Some(Rebinding {
+                                action,
+                                index: None,
+                            });
+                        }
+                    });
+
+                    if let Some(index) = removed {
+                        controls.remove_binding(action, index);
+                        *rebinding = None;
+                        changed = true;
+                    }
+
+                    if conflicting_actions.is_empty() {
+                        ui.label("");
+                    } else {
+                        let names = conflicting_actions
+                            .iter()
+                            .map(|action| {
+                                localization.localize_with_args(
+                                    action.message_id(),
+                                    &action.message_arguments(),
+                                )
+                            })
+                            .collect::<Vec<_>>();
+                        let mut arguments = FluentArgs::new();
+                        arguments.set("actions", names.join(", "));
+                        ui.colored_label(
+                            ui.visuals().warn_fg_color,
+                            localization.localize_with_args(MessageId::ControlsConflict, &arguments),
+                        );
+                    }
+                    ui.end_row();
+                }
+            });
+
+            ui.horizontal(|ui| {
+                if rebinding.is_some()
+                    && ui
+                        .button(localization.localize(MessageId::ControlsCancel))
+                        .clicked()
+                {
+                    *rebinding = None;
+                }
+
+                if ui
+                    .button(localization.localize(MessageId::ControlsReset))
+                    .clicked()
+                {
+                    *controls = Controls::default();
+                    *rebinding = None;
+                    changed = true;
+                }
+
+                if ui.button(localization.localize(MessageId::MenuBack)).clicked() {
+                    *rebinding = None;
+                    state.pop().ok();
+                }
+            });
+        });
+
+    if changed {
+        controls.save();
+    }
+}
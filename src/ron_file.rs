@@ -0,0 +1,63 @@
+use bevy::prelude::*;
+use ron::ser::PrettyConfig;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use thiserror::Error;
+
+pub fn to_ron<T: Serialize>(value: &T) -> Result<String, RonFileError> {
+    Ok(ron::ser::to_string_pretty(value, PrettyConfig::default())?)
+}
+
+pub fn from_ron<T: DeserializeOwned>(ron_string: &str) -> Result<T, RonFileError> {
+    Ok(ron::from_str(ron_string)?)
+}
+
+/// Returns `None` if there is no such file (yet).
+pub fn read_ron_file<T: DeserializeOwned>(
+    path: impl AsRef<Path>,
+) -> Result<Option<T>, RonFileError> {
+    match fs::read_to_string(path) {
+        Ok(ron_string) => Ok(Some(from_ron(&ron_string)?)),
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// Creates the directory the file is in, if it doesn't exist yet.
+pub fn write_ron_file<T: Serialize>(path: impl AsRef<Path>, value: &T) -> Result<(), RonFileError> {
+    let path = path.as_ref();
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+    fs::write(path, to_ron(value)?)?;
+    Ok(())
+}
+
+/// Writes a file with preferences, like the settings. Failing to do so is not fatal,
+/// so errors are only logged. `description` says what is saved, like "settings".
+pub fn save_ron_file<T: Serialize>(path: impl AsRef<Path>, value: &T, description: &str) {
+    let path = path.as_ref();
+    if let Err(error) = write_ron_file(path, value) {
+        error!(
+            "Could not save {} to `{}`: {}",
+            description,
+            path.display(),
+            error
+        );
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum RonFileError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("Could not write RON: {0}")]
+    Serializing(#[from] ron::Error),
+
+    #[error("Could not read RON: {0}")]
+    Deserializing(#[from] ron::error::SpannedError),
+}
@@ -0,0 +1,560 @@
+use crate::camera::{CameraAutopilot, CameraBookmarks, CameraZoom, MainCamera};
+use crate::economy::{Cargo, Demand, Inventory, Production};
+use crate::input::{Action, ActionRes};
+use crate::ron_file::{self, RonFileError};
+use crate::transport::{
+    spawn_ship, spawn_transport_line, LineDrawing, LineTraversal, Ship, TransportLine,
+};
+use crate::{spawn_planet, GameState, Planet};
+use bevy::ecs::system::CommandQueue;
+use bevy::math::Vec3Swizzles;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use thiserror::Error;
+
+/// Version of the save file format. Bump this whenever [SaveGame] changes.
+/// Version 2 added the camera bookmarks.
+const SAVE_FORMAT_VERSION: u32 = 2;
+/// Oldest version of the save file format that can still be loaded.
+/// What was added to [SaveGame] since then gets its default value.
+const OLDEST_SAVE_FORMAT_VERSION: u32 = 1;
+
+pub const QUICKSAVE_PATH: &str = "saves/quicksave.ron";
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SaveGameRequest>()
+            .add_system(save_game_system)
+            .add_system_set(
+                // At the end, so that the galaxy generator still sees the pending load, and
+                // skips generating a galaxy. Exclusive systems can't be ordered after it otherwise.
+                SystemSet::on_enter(GameState::Main).with_system(load_pending_save_system.at_end()),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Main)
+                    .with_system(quicksave_system)
+                    .with_system(quickload_system),
+            );
+    }
+}
+
+/// Send this to save the game to the quicksave file.
+pub struct SaveGameRequest;
+
+/// Save that should be loaded when entering [GameState::Main], instead of generating a new galaxy.
+#[derive(Resource)]
+pub struct PendingLoad(pub SaveGame);
+
+/// Everything needed to restore a game.
+/// Entities refer to each other by their index in these lists.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SaveGame {
+    pub version: u32,
+    pub planets: Vec<SavedPlanet>,
+    pub lines: Vec<SavedLine>,
+    pub ships: Vec<SavedShip>,
+    pub camera: SavedCamera,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SavedPlanet {
+    pub name: String,
+    pub position: Vec2,
+    pub radius: f32,
+    pub inventory: Inventory,
+    pub production: Production,
+    pub demand: Demand,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SavedLine {
+    /// Indices into [SaveGame::planets].
+    pub stops: Vec<usize>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SavedShip {
+    /// Index into [SaveGame::lines].
+    pub line: usize,
+    pub position: Vec2,
+    pub speed: f32,
+    pub traversal: LineTraversal,
+    pub target_stop: usize,
+    pub forwards: bool,
+    pub cargo: Cargo,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SavedCamera {
+    pub position: Vec2,
+    pub scale: f32,
+    /// Missing from version 1 save files.
+    #[serde(default)]
+    pub bookmarks: CameraBookmarks,
+}
+
+/// Only the version of a save file. Read before the rest of the file,
+/// so that files from other versions can be reported as such, instead of as broken files.
+#[derive(Deserialize)]
+struct SaveGameVersion {
+    version: u32,
+}
+
+impl SaveGame {
+    pub fn to_ron(&self) -> Result<String, SaveError> {
+        Ok(ron_file::to_ron(self)?)
+    }
+
+    pub fn from_ron(ron_string: &str) -> Result<SaveGame, SaveError> {
+        let SaveGameVersion { version } = ron_file::from_ron(ron_string)?;
+        if !(OLDEST_SAVE_FORMAT_VERSION..=SAVE_FORMAT_VERSION).contains(&version) {
+            return Err(SaveError::UnsupportedVersion {
+                found: version,
+                oldest: OLDEST_SAVE_FORMAT_VERSION,
+                newest: SAVE_FORMAT_VERSION,
+            });
+        }
+
+        Ok(ron_file::from_ron(ron_string)?)
+    }
+
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> Result<(), SaveError> {
+        Ok(ron_file::write_ron_file(path, self)?)
+    }
+
+    pub fn read_from_file(path: impl AsRef<Path>) -> Result<SaveGame, SaveError> {
+        let ron_string = fs::read_to_string(path).map_err(RonFileError::from)?;
+        SaveGame::from_ron(&ron_string)
+    }
+}
+
+/// Collects the state of the game world into a [SaveGame].
+pub fn save_world(world: &mut World) -> SaveGame {
+    let mut planet_indices = HashMap::default();
+    let planets = world
+        .query::<(
+            Entity,
+            &Planet,
+            &Transform,
+            &Inventory,
+            &Production,
+            &Demand,
+        )>()
+        .iter(world)
+        .enumerate()
+        .map(
+            |(index, (entity, planet, transform, inventory, production, demand))| {
+                planet_indices.insert(entity, index);
+                SavedPlanet {
+                    name: planet.name.clone(),
+                    position: transform.translation.xy(),
+                    radius: planet.radius,
+                    inventory: inventory.clone(),
+                    production: production.clone(),
+                    demand: demand.clone(),
+                }
+            },
+        )
+        .collect();
+
+    let mut line_indices = HashMap::default();
+    let lines = world
+        .query::<(Entity, &TransportLine)>()
+        .iter(world)
+        .enumerate()
+        .map(|(index, (entity, line))| {
+            line_indices.insert(entity, index);
+            SavedLine {
+                stops: line
+                    .stops
+                    .iter()
+                    .filter_map(|stop| planet_indices.get(stop).copied())
+                    .collect(),
+            }
+        })
+        .collect();
+
+    let ships = world
+        .query::<(&Ship, &Transform, &Cargo)>()
+        .iter(world)
+        .filter_map(|(ship, transform, cargo)| {
+            Some(SavedShip {
+                line: *line_indices.get(&ship.line)?,
+                position: transform.translation.xy(),
+                speed: ship.speed,
+                traversal: ship.traversal,
+                target_stop: ship.target_stop,
+                forwards: ship.forwards,
+                cargo: cargo.clone(),
+            })
+        })
+        .collect();
+
+    let bookmarks = world.resource::<CameraBookmarks>().clone();
+    let camera = world
+        .query_filtered::<(&Transform, &OrthographicProjection), With<MainCamera>>()
+        .get_single(world)
+        .map(|(transform, projection)| SavedCamera {
+            position: transform.translation.xy(),
+            scale: projection.scale,
+            bookmarks: bookmarks.clone(),
+        })
+        .unwrap_or(SavedCamera {
+            position: Vec2::ZERO,
+            scale: 1.0,
+            bookmarks,
+        });
+
+    SaveGame {
+        version: SAVE_FORMAT_VERSION,
+        planets,
+        lines,
+        ships,
+        camera,
+    }
+}
+
+/// Replaces the planets, transport lines and ships in the world with the ones in the [SaveGame],
+/// and moves the camera to where it was when the game was saved.
+pub fn load_world(world: &mut World, save: &SaveGame) {
+    let existing_entities = world
+        .query_filtered::<Entity, Or<(With<Planet>, With<TransportLine>, With<Ship>)>>()
+        .iter(world)
+        .collect::<Vec<_>>();
+
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, world);
+
+    commands.remove_resource::<LineDrawing>();
+
+    for entity in existing_entities {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let planets = save
+        .planets
+        .iter()
+        .map(|saved| {
+            let planet = spawn_planet(
+                &mut commands,
+                Planet {
+                    name: saved.name.clone(),
+                    radius: saved.radius,
+                },
+                saved.position,
+                saved.production.clone(),
+                saved.demand.clone(),
+            );
+            commands.entity(planet).insert(saved.inventory.clone());
+            planet
+        })
+        .collect::<Vec<_>>();
+
+    let lines = save
+        .lines
+        .iter()
+        .enumerate()
+        .map(|(index, saved)| {
+            let stops = saved
+                .stops
+                .iter()
+                .filter_map(|index| planets.get(*index).copied())
+                .collect();
+            let stop_positions = saved
+                .stops
+                .iter()
+                .filter_map(|index| save.planets.get(*index))
+                .map(|planet| planet.position)
+                .collect::<Vec<_>>();
+            // Lines don't store how they are travelled, their ships do.
+            let traversal = save
+                .ships
+                .iter()
+                .find(|ship| ship.line == index)
+                .map(|ship| ship.traversal)
+                .unwrap_or_default();
+            spawn_transport_line(&mut commands, stops, &stop_positions, traversal)
+        })
+        .collect::<Vec<_>>();
+
+    for saved in save.ships.iter() {
+        let Some(line) = lines.get(saved.line) else { continue; };
+
+        let ship = spawn_ship(
+            &mut commands,
+            *line,
+            saved.position,
+            saved.speed,
+            saved.traversal,
+        );
+        commands
+            .entity(ship)
+            .insert(Ship {
+                line: *line,
+                speed: saved.speed,
+                traversal: saved.traversal,
+                target_stop: saved.target_stop,
+                forwards: saved.forwards,
+            })
+            .insert(saved.cargo.clone());
+    }
+
+    queue.apply(world);
+
+    world.insert_resource(CameraAutopilot::Off);
+    world.insert_resource(save.camera.bookmarks.clone());
+
+    let mut camera_query = world.query_filtered::<
+        (&mut Transform, &mut OrthographicProjection, &mut CameraZoom),
+        With<MainCamera>,
+    >();
+    if let Ok((mut transform, mut projection, mut zoom)) = camera_query.get_single_mut(world) {
+        transform.translation.x = save.camera.position.x;
+        transform.translation.y = save.camera.position.y;
+        projection.scale = save.camera.scale;
+        zoom.target_scale = save.camera.scale;
+    }
+}
+
+fn quicksave_system(actions: ActionRes, mut save_requests: EventWriter<SaveGameRequest>) {
+    if actions.just_pressed(Action::QuickSave) {
+        save_requests.send(SaveGameRequest);
+    }
+}
+
+fn save_game_system(world: &mut World) {
+    let requested = world
+        .resource_mut::<Events<SaveGameRequest>>()
+        .drain()
+        .count()
+        > 0;
+    if !requested {
+        return;
+    }
+
+    match save_world(world).write_to_file(QUICKSAVE_PATH) {
+        Ok(()) => info!("Saved game to `{}`", QUICKSAVE_PATH),
+        Err(error) => error!("Could not save game to `{}`: {}", QUICKSAVE_PATH, error),
+    }
+}
+
+fn quickload_system(world: &mut World) {
+    if !world
+        .resource::<ActionState<Action>>()
+        .just_pressed(Action::QuickLoad)
+    {
+        return;
+    }
+
+    match SaveGame::read_from_file(QUICKSAVE_PATH) {
+        Ok(save) => {
+            load_world(world, &save);
+            info!("Loaded game from `{}`", QUICKSAVE_PATH);
+        }
+        Err(error) => error!("Could not load game from `{}`: {}", QUICKSAVE_PATH, error),
+    }
+}
+
+fn load_pending_save_system(world: &mut World) {
+    if let Some(PendingLoad(save)) = world.remove_resource::<PendingLoad>() {
+        load_world(world, &save);
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum SaveError {
+    #[error(transparent)]
+    File(#[from] RonFileError),
+
+    #[error("Save file has version {found}, but only versions {oldest} to {newest} are supported")]
+    UnsupportedVersion {
+        found: u32,
+        oldest: u32,
+        newest: u32,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::Good;
+    use crate::galaxy::{GalaxyAssets, GalaxyConfig, GalaxyPlugin};
+
+    fn test_world() -> World {
+        let mut world = World::new();
+        world.insert_resource(CameraBookmarks::default());
+        world.spawn((
+            Transform::from_xyz(120.0, -40.0, 999.0),
+            OrthographicProjection {
+                scale: 2.5,
+                ..default()
+            },
+            CameraZoom { target_scale: 2.5 },
+            MainCamera,
+        ));
+
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &world);
+        let mut production = Production::default();
+        production.rates.insert(Good::Ore, 0.5);
+        let mut demand = Demand::default();
+        demand.rates.insert(Good::Food, 0.25);
+        let positions = [Vec2::new(-100.0, 20.0), Vec2::new(300.0, 75.5)];
+        let planets = positions
+            .iter()
+            .enumerate()
+            .map(|(index, position)| {
+                let planet = Planet {
+                    name: format!("Planet {}", index),
+                    radius: 10.0 + index as f32,
+                };
+                spawn_planet(
+                    &mut commands,
+                    planet,
+                    *position,
+                    production.clone(),
+                    demand.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let line = spawn_transport_line(
+            &mut commands,
+            planets.clone(),
+            &positions,
+            LineTraversal::Loop,
+        );
+        let ship = spawn_ship(
+            &mut commands,
+            line,
+            Vec2::new(12.0, 34.0),
+            75.0,
+            LineTraversal::Loop,
+        );
+        queue.apply(&mut world);
+
+        world
+            .get_mut::<Inventory>(planets[0])
+            .unwrap()
+            .add(Good::Ore, 42.5);
+        world
+            .get_mut::<Cargo>(ship)
+            .unwrap()
+            .goods
+            .add(Good::Food, 7.0);
+        let mut ship = world.get_mut::<Ship>(ship).unwrap();
+        ship.target_stop = 1;
+        ship.forwards = false;
+
+        world
+    }
+
+    #[test]
+    fn world_survives_round_trip() {
+        let mut world = test_world();
+        let saved = save_world(&mut world);
+        assert_eq!(saved.planets.len(), 2);
+        assert_eq!(saved.lines.len(), 1);
+        assert_eq!(saved.ships.len(), 1);
+        assert_eq!(saved.camera.position, Vec2::new(120.0, -40.0));
+
+        let read = SaveGame::from_ron(&saved.to_ron().unwrap()).unwrap();
+        assert_eq!(read, saved);
+
+        load_world(&mut world, &read);
+        assert_eq!(save_world(&mut world), saved);
+    }
+
+    #[test]
+    fn version_1_saves_still_load() {
+        /// [SavedCamera] before the bookmarks were added.
+        #[derive(Serialize)]
+        struct SavedCameraV1 {
+            position: Vec2,
+            scale: f32,
+        }
+
+        #[derive(Serialize)]
+        struct SaveGameV1 {
+            version: u32,
+            planets: Vec<SavedPlanet>,
+            lines: Vec<SavedLine>,
+            ships: Vec<SavedShip>,
+            camera: SavedCameraV1,
+        }
+
+        let save = save_world(&mut test_world());
+        let old_save = SaveGameV1 {
+            version: 1,
+            planets: save.planets.clone(),
+            lines: save.lines.clone(),
+            ships: save.ships.clone(),
+            camera: SavedCameraV1 {
+                position: save.camera.position,
+                scale: save.camera.scale,
+            },
+        };
+
+        let read = SaveGame::from_ron(&ron_file::to_ron(&old_save).unwrap()).unwrap();
+
+        assert_eq!(read.planets, save.planets);
+        assert_eq!(read.camera.bookmarks, CameraBookmarks::default());
+    }
+
+    #[test]
+    fn other_versions_are_rejected() {
+        for version in [0, SAVE_FORMAT_VERSION + 1] {
+            let mut save = save_world(&mut test_world());
+            save.version = version;
+
+            let result = SaveGame::from_ron(&save.to_ron().unwrap());
+            assert!(
+                matches!(
+                    result,
+                    Err(SaveError::UnsupportedVersion { found, .. }) if found == version
+                ),
+                "{:?}",
+                result
+            );
+        }
+    }
+
+    #[test]
+    fn pending_save_replaces_the_new_galaxy() {
+        let save = save_world(&mut test_world());
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin::default())
+            .add_plugin(GalaxyPlugin)
+            .add_plugin(SavePlugin)
+            .add_state(GameState::Main)
+            .init_resource::<ActionState<Action>>()
+            .init_resource::<CameraBookmarks>();
+        let config = app
+            .world
+            .resource_mut::<Assets<GalaxyConfig>>()
+            .add(GalaxyConfig {
+                seed: 7,
+                planet_count: 10,
+                radius: 1000.0,
+                min_planet_spacing: 20.0,
+                min_planet_radius: 10.0,
+                max_planet_radius: 20.0,
+                max_good_rate: 1.0,
+            });
+        app.insert_resource(GalaxyAssets { config })
+            .insert_resource(PendingLoad(save.clone()));
+
+        app.update();
+
+        let planet_count = app.world.query::<&Planet>().iter(&app.world).count();
+        assert_eq!(planet_count, save.planets.len());
+        assert!(app.world.get_resource::<PendingLoad>().is_none());
+    }
+}
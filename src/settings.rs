@@ -0,0 +1,37 @@
+use crate::ron_file::{read_ron_file, save_ron_file};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const SETTINGS_PATH: &str = "settings.ron";
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = match read_ron_file::<Settings>(SETTINGS_PATH) {
+            Ok(settings) => settings.unwrap_or_default(),
+            Err(error) => {
+                warn!("Using default settings: {}", error);
+                Settings::default()
+            }
+        };
+
+        app.insert_resource(settings);
+    }
+}
+
+/// Player preferences that are kept between sessions.
+#[derive(Resource, Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct Settings {
+    /// Identifier of the language the player picked, like `en-US`.
+    /// `None` as long as the player hasn't picked one.
+    pub language: Option<String>,
+}
+
+impl Settings {
+    /// Writes the settings to disk. Failing to do so is not fatal, so errors are only logged.
+    pub fn save(&self) {
+        save_ron_file(SETTINGS_PATH, self, "settings");
+    }
+}
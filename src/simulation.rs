@@ -0,0 +1,67 @@
+use crate::GameState;
+use bevy::ecs::schedule::ShouldRun;
+use bevy::prelude::*;
+use bevy::time::FixedTimestep;
+
+/// Time in seconds between two ticks of the game simulation.
+pub const SIMULATION_TIMESTEP: f32 = 1.0 / 30.0;
+
+/// Sets up the [SimulationStage]. Every plugin with simulation systems adds it if it isn't there
+/// yet, so that those plugins also work on their own, for example in a headless test app.
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_stage_after(
+            CoreStage::Update,
+            SimulationStage,
+            SystemStage::parallel()
+                .with_run_criteria(FixedTimestep::step(SIMULATION_TIMESTEP as f64)),
+        );
+    }
+}
+
+/// Stage in which the game simulation runs, once every [SIMULATION_TIMESTEP].
+/// Because the simulation doesn't depend on the frame time, it is deterministic.
+#[derive(StageLabel)]
+pub struct SimulationStage;
+
+/// Run criteria for the systems in the [SimulationStage].
+/// Without game states, like in a headless test app, the simulation always runs.
+pub fn simulation_running(state: Option<Res<State<GameState>>>) -> ShouldRun {
+    match state {
+        Some(state) if *state.current() != GameState::Main => ShouldRun::No,
+        _ => ShouldRun::Yes,
+    }
+}
+
+#[cfg(test)]
+pub mod testing {
+    use super::*;
+    use bevy::time::TimeUpdateStrategy;
+    use bevy::utils::{Duration, Instant};
+
+    /// An app without a window, in which time only passes in [run_ticks].
+    pub fn headless_app() -> App {
+        let start = Instant::now();
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualInstant(start));
+        // Start the clock, so that the first update already advances time.
+        app.world.resource_mut::<Time>().update_with_instant(start);
+        app
+    }
+
+    /// Updates the app once for every tick, so that the simulation advances exactly `ticks` ticks.
+    pub fn run_ticks(app: &mut App, ticks: usize) {
+        // A little extra per tick, so that rounding never makes the fixed timestep skip a tick.
+        let tick = Duration::from_secs_f32(SIMULATION_TIMESTEP) + Duration::from_micros(1);
+        for _ in 0..ticks {
+            let mut strategy = app.world.resource_mut::<TimeUpdateStrategy>();
+            if let TimeUpdateStrategy::ManualInstant(instant) = strategy.as_mut() {
+                *instant += tick;
+            }
+            app.update();
+        }
+    }
+}
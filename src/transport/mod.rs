@@ -0,0 +1,264 @@
+mod ship;
+
+use crate::input::{Action, ActionRes, GlobalCursorPosition};
+use crate::localization::{FluentArgs, Localization, MessageId};
+use crate::simulation::{simulation_running, SimulationPlugin, SimulationStage};
+use crate::{planet_under_cursor, GameState, Planet, UI_MARGIN};
+use bevy::math::Vec3Swizzles;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+pub use crate::transport::ship::{
+    ship_movement_system, spawn_ship, LineTraversal, Ship, ShipArrived, DEFAULT_SHIP_SPEED,
+    SHIP_SPEED_RANGE,
+};
+
+const LINE_WIDTH: f32 = 4.0;
+const LINE_COLOR: Color = Color::rgb(0.9, 0.75, 0.1);
+/// Lines are drawn below the planets.
+const LINE_Z: f32 = -1.0;
+
+pub struct TransportPlugin;
+
+impl Plugin for TransportPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<SimulationPlugin>() {
+            app.add_plugin(SimulationPlugin);
+        }
+
+        app.add_event::<ShipArrived>()
+            .add_system_set(
+                SystemSet::on_update(GameState::Main)
+                    .with_system(line_drawing_system)
+                    .with_system(line_drawing_ui),
+            )
+            .add_system_set_to_stage(
+                SimulationStage,
+                SystemSet::new()
+                    .with_run_criteria(simulation_running)
+                    .with_system(ship_movement_system),
+            );
+    }
+}
+
+/// A route along which goods are transported.
+/// The stops are [Planet] entities, in the order in which they are visited.
+#[derive(Component)]
+pub struct TransportLine {
+    pub stops: Vec<Entity>,
+}
+
+/// While this resource exists, the player is drawing a new [TransportLine].
+/// Each primary interaction on a planet appends that planet as a stop.
+#[derive(Resource)]
+pub struct LineDrawing {
+    stops: Vec<Entity>,
+    /// How the ship of the new line travels along it.
+    traversal: LineTraversal,
+    /// Speed of the ship of the new line, in game units per second.
+    ship_speed: f32,
+}
+
+impl Default for LineDrawing {
+    fn default() -> Self {
+        LineDrawing {
+            stops: vec![],
+            traversal: LineTraversal::default(),
+            ship_speed: DEFAULT_SHIP_SPEED,
+        }
+    }
+}
+
+fn line_drawing_system(
+    line_drawing: Option<ResMut<LineDrawing>>,
+    actions: ActionRes,
+    cursor_position: Res<GlobalCursorPosition>,
+    planets: Query<(Entity, &GlobalTransform, &Planet)>,
+) {
+    let Some(mut line_drawing) = line_drawing else { return; };
+
+    if !actions.just_pressed(Action::PrimaryInteraction) {
+        return;
+    }
+
+    let Some(planet) = planet_under_cursor(**cursor_position, planets.iter()) else { return; };
+
+    // Visiting the same planet twice in a row makes no sense.
+    if line_drawing.stops.last() != Some(&planet) {
+        line_drawing.stops.push(planet);
+    }
+}
+
+fn line_drawing_ui(
+    mut commands: Commands,
+    mut egui_context: ResMut<EguiContext>,
+    localization: Res<Localization>,
+    line_drawing: Option<ResMut<LineDrawing>>,
+    planets: Query<(&Planet, &GlobalTransform)>,
+) {
+    let Some(mut line_drawing) = line_drawing else { return; };
+
+    let (anchor, offset) =
+        localization.mirror_anchor(egui::Align2::RIGHT_TOP, (-UI_MARGIN, UI_MARGIN));
+    egui::Window::new("line_drawing")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(anchor, offset)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.with_layout(localization.vertical_layout(), |ui| {
+                ui.label(localization.localize(MessageId::TransportLineDrawingHint));
+
+                let mut arguments = FluentArgs::new();
+                arguments.set("count", line_drawing.stops.len());
+                ui.label(
+                    localization.localize_with_args(MessageId::TransportLineStopCount, &arguments),
+                );
+
+                for stop in line_drawing.stops.iter() {
+                    if let Ok((planet, _)) = planets.get(*stop) {
+                        ui.label(&planet.name);
+                    }
+                }
+
+                ui.with_layout(localization.horizontal_layout(), |ui| {
+                    ui.radio_value(
+                        &mut line_drawing.traversal,
+                        LineTraversal::PingPong,
+                        localization.localize(MessageId::LineTraversalPingPong),
+                    );
+                    ui.radio_value(
+                        &mut line_drawing.traversal,
+                        LineTraversal::Loop,
+                        localization.localize(MessageId::LineTraversalLoop),
+                    );
+                });
+                ui.add(
+                    egui::Slider::new(&mut line_drawing.ship_speed, SHIP_SPEED_RANGE)
+                        .text(localization.localize(MessageId::TransportLineShipSpeed)),
+                );
+
+                ui.with_layout(localization.horizontal_layout(), |ui| {
+                    let confirm_button = ui.add_enabled(
+                        line_drawing.stops.len() >= 2,
+                        egui::Button::new(localization.localize(MessageId::ConfirmTransportLine)),
+                    );
+                    if confirm_button.clicked() {
+                        let stop_positions = line_drawing
+                            .stops
+                            .iter()
+                            .filter_map(|stop| planets.get(*stop).ok())
+                            .map(|(_, transform)| transform.translation().xy())
+                            .collect::<Vec<_>>();
+
+                        let line = spawn_transport_line(
+                            &mut commands,
+                            line_drawing.stops.clone(),
+                            &stop_positions,
+                            line_drawing.traversal,
+                        );
+                        if let Some(first_stop_position) = stop_positions.first() {
+                            spawn_ship(
+                                &mut commands,
+                                line,
+                                *first_stop_position,
+                                line_drawing.ship_speed,
+                                line_drawing.traversal,
+                            );
+                        }
+                        commands.remove_resource::<LineDrawing>();
+                    }
+
+                    if ui
+                        .button(localization.localize(MessageId::CancelTransportLine))
+                        .clicked()
+                    {
+                        commands.remove_resource::<LineDrawing>();
+                    }
+                });
+            });
+        });
+}
+
+/// Spawns a [TransportLine], with a sprite for every segment between two consecutive stops.
+/// Lines that are travelled in a loop also get a segment from the last stop back to the first.
+pub fn spawn_transport_line(
+    commands: &mut Commands,
+    stops: Vec<Entity>,
+    stop_positions: &[Vec2],
+    traversal: LineTraversal,
+) -> Entity {
+    let segments = line_segments(stop_positions, traversal);
+    commands
+        .spawn(SpatialBundle::default())
+        .insert(TransportLine { stops })
+        .with_children(|parent| {
+            for (start, end) in segments {
+                let difference = end - start;
+                let middle = start + difference / 2.0;
+
+                parent.spawn(SpriteBundle {
+                    sprite: Sprite {
+                        color: LINE_COLOR,
+                        custom_size: Some(Vec2::new(difference.length(), LINE_WIDTH)),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(middle.extend(LINE_Z))
+                        .with_rotation(Quat::from_rotation_z(difference.y.atan2(difference.x))),
+                    ..default()
+                });
+            }
+        })
+        .id()
+}
+
+/// The start and end of every segment of a line along the given stops.
+fn line_segments(stop_positions: &[Vec2], traversal: LineTraversal) -> Vec<(Vec2, Vec2)> {
+    let mut segments = stop_positions
+        .iter()
+        .zip(stop_positions.iter().skip(1))
+        .map(|(start, end)| (*start, *end))
+        .collect::<Vec<_>>();
+
+    // With only two stops, the way back is the segment that is already there.
+    if traversal == LineTraversal::Loop && stop_positions.len() > 2 {
+        if let (Some(last), Some(first)) = (stop_positions.last(), stop_positions.first()) {
+            segments.push((*last, *first));
+        }
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STOPS: [Vec2; 3] = [
+        Vec2::new(0.0, 0.0),
+        Vec2::new(10.0, 0.0),
+        Vec2::new(10.0, 10.0),
+    ];
+
+    #[test]
+    fn looping_lines_are_closed() {
+        assert_eq!(
+            line_segments(&STOPS, LineTraversal::Loop),
+            [
+                (STOPS[0], STOPS[1]),
+                (STOPS[1], STOPS[2]),
+                (STOPS[2], STOPS[0])
+            ]
+        );
+        assert_eq!(
+            line_segments(&STOPS[..2], LineTraversal::Loop),
+            [(STOPS[0], STOPS[1])]
+        );
+    }
+
+    #[test]
+    fn ping_pong_lines_are_open() {
+        assert_eq!(
+            line_segments(&STOPS, LineTraversal::PingPong),
+            [(STOPS[0], STOPS[1]), (STOPS[1], STOPS[2])]
+        );
+    }
+}
@@ -0,0 +1,329 @@
+use crate::economy::{Cargo, DEFAULT_CARGO_CAPACITY};
+use crate::simulation::SIMULATION_TIMESTEP;
+use crate::transport::TransportLine;
+use crate::Planet;
+use bevy::math::Vec3Swizzles;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Speed in game units per second.
+pub const DEFAULT_SHIP_SPEED: f32 = 100.0;
+/// Slowest and fastest speed the player can pick for the ships of a new line.
+pub const SHIP_SPEED_RANGE: std::ops::RangeInclusive<f32> = 25.0..=400.0;
+
+const SHIP_SIZE: f32 = 8.0;
+const SHIP_COLOR: Color = Color::rgb(1.0, 0.85, 0.0);
+/// Ships are drawn above the planets.
+const SHIP_Z: f32 = 1.0;
+
+/// How a [Ship] continues when it reaches the last stop of its [TransportLine].
+#[derive(Clone, Copy, Eq, PartialEq, Default, Debug, Serialize, Deserialize)]
+pub enum LineTraversal {
+    /// Travel from the last stop straight back to the first stop.
+    Loop,
+    /// Turn around, and visit the stops in reverse order.
+    #[default]
+    PingPong,
+}
+
+/// Sent when a [Ship] arrives at one of the stops of its line.
+pub struct ShipArrived {
+    pub ship: Entity,
+    pub planet: Entity,
+}
+
+/// A vehicle (a "bee") that travels from stop to stop along a [TransportLine].
+#[derive(Component)]
+pub struct Ship {
+    pub line: Entity,
+    /// Speed in game units per second.
+    pub speed: f32,
+    pub traversal: LineTraversal,
+    /// Index into the stops of the line, of the stop the ship is currently travelling towards.
+    pub target_stop: usize,
+    /// Whether the ship is travelling towards the end of the line.
+    /// Only ever `false` for [LineTraversal::PingPong].
+    pub forwards: bool,
+}
+
+impl Ship {
+    pub fn new(line: Entity, speed: f32, traversal: LineTraversal) -> Self {
+        Ship {
+            line,
+            speed,
+            traversal,
+            target_stop: 0,
+            forwards: true,
+        }
+    }
+
+    /// Moves the ship `distance` game units along the line, starting from `position`.
+    /// Returns the new position of the ship.
+    ///
+    /// `stop_positions` are the positions of the stops of the line, in order.
+    /// `on_arrival` is called with the index of every stop the ship arrives at along the way.
+    pub fn advance(
+        &mut self,
+        mut position: Vec2,
+        mut distance: f32,
+        stop_positions: &[Vec2],
+        mut on_arrival: impl FnMut(usize),
+    ) -> Vec2 {
+        if stop_positions.len() < 2 {
+            // Nowhere to go.
+            return position;
+        }
+
+        // Bounded, so that stops on top of each other can't keep us here forever.
+        for _ in 0..2 * stop_positions.len() {
+            if distance <= 0.0 {
+                break;
+            }
+
+            let target_stop = self.target_stop % stop_positions.len();
+            let target = stop_positions[target_stop];
+            let to_target = target - position;
+            let distance_to_target = to_target.length();
+
+            if distance_to_target > distance {
+                return position + to_target / distance_to_target * distance;
+            }
+
+            position = target;
+            distance -= distance_to_target;
+            on_arrival(target_stop);
+            self.select_next_stop(stop_positions.len());
+        }
+
+        position
+    }
+
+    fn select_next_stop(&mut self, stop_amount: usize) {
+        let last_stop = stop_amount - 1;
+
+        match self.traversal {
+            LineTraversal::Loop => {
+                self.forwards = true;
+                self.target_stop = (self.target_stop + 1) % stop_amount;
+            }
+            LineTraversal::PingPong => {
+                if self.target_stop >= last_stop {
+                    self.forwards = false;
+                } else if self.target_stop == 0 {
+                    self.forwards = true;
+                }
+
+                if self.forwards {
+                    self.target_stop += 1;
+                } else {
+                    self.target_stop = self.target_stop.min(last_stop) - 1;
+                }
+            }
+        }
+    }
+}
+
+/// Spawns a [Ship] on the given line, at the position of its first stop.
+pub fn spawn_ship(
+    commands: &mut Commands,
+    line: Entity,
+    position: Vec2,
+    speed: f32,
+    traversal: LineTraversal,
+) -> Entity {
+    commands
+        .spawn(SpriteBundle {
+            sprite: Sprite {
+                color: SHIP_COLOR,
+                custom_size: Some(Vec2::splat(SHIP_SIZE)),
+                ..default()
+            },
+            transform: Transform::from_translation(position.extend(SHIP_Z)),
+            ..default()
+        })
+        .insert(Ship::new(line, speed, traversal))
+        .insert(Cargo::new(DEFAULT_CARGO_CAPACITY))
+        .id()
+}
+
+/// Moves every ship along its line by a single [SIMULATION_TIMESTEP].
+pub fn ship_movement_system(
+    mut ships: Query<(Entity, &mut Ship, &mut Transform), Without<Planet>>,
+    lines: Query<&TransportLine>,
+    planets: Query<(Entity, &Transform), With<Planet>>,
+    mut arrivals: EventWriter<ShipArrived>,
+) {
+    for (ship_entity, mut ship, mut transform) in ships.iter_mut() {
+        let Ok(line) = lines.get(ship.line) else { continue; };
+
+        let (stop_entities, stop_positions): (Vec<Entity>, Vec<Vec2>) = line
+            .stops
+            .iter()
+            .filter_map(|stop| planets.get(*stop).ok())
+            .map(|(planet, planet_transform)| (planet, planet_transform.translation.xy()))
+            .unzip();
+
+        let distance = ship.speed * SIMULATION_TIMESTEP;
+        let position = ship.advance(
+            transform.translation.xy(),
+            distance,
+            &stop_positions,
+            |stop_index| {
+                arrivals.send(ShipArrived {
+                    ship: ship_entity,
+                    planet: stop_entities[stop_index],
+                })
+            },
+        );
+
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::testing::{headless_app, run_ticks};
+    use crate::simulation::{SimulationPlugin, SimulationStage};
+
+    const TWO_STOPS: [Vec2; 2] = [Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0)];
+    const THREE_STOPS: [Vec2; 3] = [
+        Vec2::new(0.0, 0.0),
+        Vec2::new(10.0, 0.0),
+        Vec2::new(20.0, 0.0),
+    ];
+
+    /// Moves a new ship from the first stop in steps of one game unit,
+    /// and returns the stops it arrived at, in order.
+    fn visited_stops(traversal: LineTraversal, stops: &[Vec2], distance: usize) -> Vec<usize> {
+        let mut ship = Ship::new(Entity::from_raw(0), 1.0, traversal);
+        let mut position = stops[0];
+        let mut arrivals = Vec::new();
+        for _ in 0..distance {
+            position = ship.advance(position, 1.0, stops, |stop| arrivals.push(stop));
+        }
+        arrivals
+    }
+
+    #[test]
+    fn loop_returns_to_first_stop() {
+        assert_eq!(
+            visited_stops(LineTraversal::Loop, &TWO_STOPS, 30),
+            [0, 1, 0, 1]
+        );
+        assert_eq!(
+            visited_stops(LineTraversal::Loop, &THREE_STOPS, 50),
+            [0, 1, 2, 0, 1]
+        );
+    }
+
+    #[test]
+    fn ping_pong_turns_around_at_the_ends() {
+        assert_eq!(
+            visited_stops(LineTraversal::PingPong, &TWO_STOPS, 30),
+            [0, 1, 0, 1]
+        );
+        assert_eq!(
+            visited_stops(LineTraversal::PingPong, &THREE_STOPS, 50),
+            [0, 1, 2, 1, 0, 1]
+        );
+    }
+
+    #[test]
+    fn overshoot_continues_past_several_stops() {
+        let mut ship = Ship::new(Entity::from_raw(0), 1.0, LineTraversal::Loop);
+        let mut arrivals = Vec::new();
+
+        let position = ship.advance(THREE_STOPS[0], 25.0, &THREE_STOPS, |stop| {
+            arrivals.push(stop)
+        });
+
+        assert_eq!(arrivals, [0, 1, 2]);
+        assert_eq!(position, Vec2::new(15.0, 0.0));
+        assert_eq!(ship.target_stop, 0);
+    }
+
+    #[test]
+    fn stacked_stops_do_not_loop_forever() {
+        let stops = [Vec2::new(5.0, 5.0); 3];
+        let mut ship = Ship::new(Entity::from_raw(0), 1.0, LineTraversal::Loop);
+        let mut arrivals = 0;
+
+        let position = ship.advance(stops[0], 100.0, &stops, |_| arrivals += 1);
+
+        assert_eq!(position, stops[0]);
+        assert_eq!(arrivals, 2 * stops.len());
+    }
+
+    #[test]
+    fn ship_stays_put_without_two_stops() {
+        let start = Vec2::new(3.0, 4.0);
+        for stops in [&[][..], &[Vec2::new(10.0, 0.0)][..]] {
+            let mut ship = Ship::new(Entity::from_raw(0), 1.0, LineTraversal::Loop);
+            let position = ship.advance(start, 100.0, stops, |_| panic!("arrived nowhere"));
+            assert_eq!(position, start);
+        }
+    }
+
+    #[derive(Resource, Default)]
+    struct ArrivedPlanets(Vec<Entity>);
+
+    fn record_arrivals(
+        mut arrivals: EventReader<ShipArrived>,
+        mut arrived_planets: ResMut<ArrivedPlanets>,
+    ) {
+        arrived_planets
+            .0
+            .extend(arrivals.iter().map(|arrival| arrival.planet));
+    }
+
+    #[test]
+    fn ships_move_one_step_per_tick() {
+        let mut app = headless_app();
+        app.add_plugin(SimulationPlugin)
+            .add_event::<ShipArrived>()
+            .init_resource::<ArrivedPlanets>()
+            .add_system_to_stage(SimulationStage, ship_movement_system)
+            .add_system_to_stage(SimulationStage, record_arrivals.after(ship_movement_system));
+
+        let mut spawn_planet = |x| {
+            let planet = Planet {
+                name: String::new(),
+                radius: 1.0,
+            };
+            app.world
+                .spawn((planet, Transform::from_xyz(x, 0.0, 0.0)))
+                .id()
+        };
+        let first = spawn_planet(0.0);
+        // Not a whole number of steps away, so that rounding can't decide when the ship arrives.
+        let second = spawn_planet(10.5);
+        let line = app
+            .world
+            .spawn(TransportLine {
+                stops: vec![first, second],
+            })
+            .id();
+        // One game unit per tick.
+        let speed = 1.0 / SIMULATION_TIMESTEP;
+        let ship = app
+            .world
+            .spawn((
+                Ship::new(line, speed, LineTraversal::PingPong),
+                Transform::default(),
+            ))
+            .id();
+
+        run_ticks(&mut app, 10);
+        let position = app.world.get::<Transform>(ship).unwrap().translation;
+        assert!((position.x - 10.0).abs() < 1e-3, "ship at {}", position);
+        assert_eq!(app.world.resource::<ArrivedPlanets>().0, [first]);
+
+        // Arrives at the second stop, and turns back the rest of the step.
+        run_ticks(&mut app, 1);
+        let position = app.world.get::<Transform>(ship).unwrap().translation;
+        assert!((position.x - 10.0).abs() < 1e-3, "ship at {}", position);
+        assert_eq!(app.world.resource::<ArrivedPlanets>().0, [first, second]);
+    }
+}